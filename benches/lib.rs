@@ -6,7 +6,7 @@ extern crate lifeguard;
 mod tests {
   use test::Bencher;
   use test::black_box;
-  use lifeguard::{Pool,Recycled,RcRecycled};
+  use lifeguard::{Pool,Recycled,RcRecycled,SyncPool,TieredPool,tiered_pool,StartingSize};
 
   const ITERATIONS : u32 = 10_000;
 
@@ -56,6 +56,34 @@ mod tests {
     });
   }
 
+  #[bench]
+  fn allocation_pooled_sync(b: &mut Bencher) {
+    let pool : SyncPool<String> = SyncPool::with_size(5);
+    b.iter(|| {
+      for _ in 0..ITERATIONS {
+        let _string = pool.new();
+        let _string = pool.new();
+        let _string = pool.new();
+        let _string = pool.new();
+        let _string = pool.new();
+      }
+    });
+  }
+
+  #[bench]
+  fn allocation_pooled_tiered(b: &mut Bencher) {
+    let pool : TieredPool<String> = tiered_pool().with(StartingSize(5)).build();
+    b.iter(|| {
+      for _ in 0..ITERATIONS {
+        let _string = pool.new();
+        let _string = pool.new();
+        let _string = pool.new();
+        let _string = pool.new();
+        let _string = pool.new();
+      }
+    });
+  }
+
   #[bench]
   fn initialized_allocation_standard(b: &mut Bencher) {
     b.iter(|| {
@@ -0,0 +1,53 @@
+extern crate lifeguard;
+use lifeguard::{Pool, RcRecycled, Recycleable};
+
+// A syntax tree node whose children come from the same pool as the node itself.
+struct Node {
+  name: String,
+  children: Vec<RcRecycled<Node>>
+}
+
+impl Recycleable for Node {
+  fn new() -> Node {
+    Node { name: String::new(), children: Vec::new() }
+  }
+
+  fn reset(&mut self) {
+    self.name.clear();
+    // Dropping the children hands them back to the pool. The pool queues them and
+    // resets them once this node is done, so a deep tree doesn't grow the stack.
+    self.children.clear();
+  }
+}
+
+fn leaf(pool: &Pool<Node>, name: &str) -> RcRecycled<Node> {
+  let mut node = pool.new_rc();
+  node.name.push_str(name);
+  node
+}
+
+fn main() {
+  let pool : Pool<Node> = Pool::with_size(0);
+
+  // `(a + b) * c`
+  {
+    let mut sum = leaf(&pool, "+");
+    sum.children.push(leaf(&pool, "a"));
+    sum.children.push(leaf(&pool, "b"));
+    let mut product = leaf(&pool, "*");
+    product.children.push(sum);
+    product.children.push(leaf(&pool, "c"));
+  }
+  println!("after a small tree: {} nodes pooled", pool.size());
+
+  // A chain far deeper than the stack could handle recursively.
+  {
+    let mut root = leaf(&pool, "0");
+    for depth in 1..1_000_000 {
+      let mut parent = leaf(&pool, &depth.to_string());
+      parent.children.push(root);
+      root = parent;
+    }
+  }
+  println!("after a deep chain: {} nodes pooled", pool.size());
+}
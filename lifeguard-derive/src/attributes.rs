@@ -0,0 +1,101 @@
+use syn::{DeriveInput, Error, Expr, Field, Ident, LitStr, Path, Result, Type};
+
+/// The settings of a field's `#[recycle(...)]` attribute.
+#[derive(Default)]
+pub struct RecycleOptions {
+  pub skip: bool,
+  pub default: Option<Expr>,
+  pub reset_with: Option<Path>
+}
+
+impl RecycleOptions {
+  pub fn of(field: &Field) -> Result<RecycleOptions> {
+    let mut options = RecycleOptions::default();
+    for attribute in field.attrs.iter().filter(|attribute| attribute.path().is_ident("recycle")) {
+      attribute.parse_nested_meta(|meta| {
+        if meta.path.is_ident("skip") {
+          options.skip = true;
+        } else if meta.path.is_ident("default") {
+          let value: LitStr = meta.value()?.parse()?;
+          options.default = Some(value.parse()?);
+        } else if meta.path.is_ident("reset_with") {
+          let value: LitStr = meta.value()?.parse()?;
+          options.reset_with = Some(value.parse()?);
+        } else {
+          return Err(meta.error("expected `skip`, `default` or `reset_with`"));
+        }
+        Ok(())
+      })?;
+    }
+    if options.skip && options.reset_with.is_some() {
+      return Err(Error::new_spanned(field, "a field can't have both `skip` and `reset_with`"));
+    }
+    Ok(options)
+  }
+}
+
+/// A source type named by a struct's `#[initialize_with(...)]` attributes.
+pub enum Source {
+  /// `#[initialize_with(source = "Type")]`: initialize from `&Type`, field by field.
+  Reference(Box<Type>),
+  /// `#[initialize_with(tuple)]`: initialize from a tuple with one element per field.
+  Tuple
+}
+
+impl Source {
+  pub fn all_of(input: &DeriveInput) -> Result<Vec<Source>> {
+    let mut sources = Vec::new();
+    for attribute in input.attrs.iter().filter(|attribute| attribute.path().is_ident("initialize_with")) {
+      attribute.parse_nested_meta(|meta| {
+        if meta.path.is_ident("source") {
+          let value: LitStr = meta.value()?.parse()?;
+          sources.push(Source::Reference(Box::new(value.parse()?)));
+        } else if meta.path.is_ident("tuple") {
+          sources.push(Source::Tuple);
+        } else {
+          return Err(meta.error("expected `source` or `tuple`"));
+        }
+        Ok(())
+      })?;
+    }
+    if sources.is_empty() {
+      return Err(Error::new_spanned(&input.ident,
+        "expected `#[initialize_with(source = \"Type\")]` or `#[initialize_with(tuple)]`"));
+    }
+    Ok(sources)
+  }
+}
+
+/// The settings of a field's `#[initialize_with(...)]` attribute.
+#[derive(Default)]
+pub struct InitializeOptions {
+  pub skip: bool,
+  pub from: Option<Ident>,
+  pub from_type: Option<Type>,
+  pub clone: bool
+}
+
+impl InitializeOptions {
+  pub fn of(field: &Field) -> Result<InitializeOptions> {
+    let mut options = InitializeOptions::default();
+    for attribute in field.attrs.iter().filter(|attribute| attribute.path().is_ident("initialize_with")) {
+      attribute.parse_nested_meta(|meta| {
+        if meta.path.is_ident("skip") {
+          options.skip = true;
+        } else if meta.path.is_ident("clone") {
+          options.clone = true;
+        } else if meta.path.is_ident("from") {
+          let value: LitStr = meta.value()?.parse()?;
+          options.from = Some(value.parse()?);
+        } else if meta.path.is_ident("from_type") {
+          let value: LitStr = meta.value()?.parse()?;
+          options.from_type = Some(value.parse()?);
+        } else {
+          return Err(meta.error("expected `skip`, `from`, `from_type` or `clone`"));
+        }
+        Ok(())
+      })?;
+    }
+    Ok(options)
+  }
+}
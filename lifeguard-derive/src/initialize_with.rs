@@ -0,0 +1,133 @@
+use proc_macro2::{Span, TokenStream, TokenTree};
+use syn::{Data, DeriveInput, Error, Generics, Ident, Member, Result, Type};
+
+use attributes::{InitializeOptions, Source};
+
+struct Target<'a> {
+  member: Member,
+  ty: &'a Type,
+  options: InitializeOptions
+}
+
+pub fn derive(input: &DeriveInput) -> Result<TokenStream> {
+  let data = match input.data {
+    Data::Struct(ref data) => data,
+    _ => return Err(Error::new_spanned(&input.ident, "`InitializeWith` can only be derived for structs"))
+  };
+  let mut targets = Vec::new();
+  for (index, field) in data.fields.iter().enumerate() {
+    let options = InitializeOptions::of(field)?;
+    if options.skip {
+      continue;
+    }
+    let member = match field.ident {
+      Some(ref ident) => Member::Named(ident.clone()),
+      None => Member::Unnamed(index.into())
+    };
+    targets.push(Target { member, ty: &field.ty, options });
+  }
+
+  let mut impls = Vec::new();
+  for source in Source::all_of(input)? {
+    impls.push(match source {
+      Source::Reference(ty) => from_reference(input, &targets, &ty)?,
+      Source::Tuple => from_tuple(input, &targets)?
+    });
+  }
+  Ok(quote!(#(#impls)*))
+}
+
+/// Returns true if `tokens` name any of the type parameters in `generics`.
+fn mentions_type_param(tokens: TokenStream, generics: &Generics) -> bool {
+  tokens.into_iter().any(|token| match token {
+    TokenTree::Ident(ref ident) => generics.type_params().any(|param| param.ident == *ident),
+    TokenTree::Group(ref group) => mentions_type_param(group.stream(), generics),
+    _ => false
+  })
+}
+
+/// Generates `InitializeWith<&Source>`, initializing each field from the source
+/// field with the same name, or the one named by `from`.
+///
+/// The macro can't see the types of the source's fields, so it assumes that each
+/// has the type of the field it initializes unless `from_type` says otherwise.
+/// That assumption is only needed for the bounds of fields whose types involve
+/// the struct's type parameters; other fields are checked where they're used.
+fn from_reference(input: &DeriveInput, targets: &[Target], source: &Type) -> Result<TokenStream> {
+  let mut generics = input.generics.clone();
+  generics.params.insert(0, parse_quote!('__source));
+  let mut statements = Vec::new();
+  for target in targets {
+    let member = &target.member;
+    let ty = target.ty;
+    let from = match target.options.from {
+      Some(ref ident) => Member::Named(ident.clone()),
+      None => member.clone()
+    };
+    let from_type = target.options.from_type.as_ref().unwrap_or(ty);
+    let needs_bound = target.options.from_type.is_some()
+      || mentions_type_param(quote!(#ty), &input.generics);
+    statements.push(if target.options.clone {
+      if needs_bound {
+        generics.make_where_clause().predicates.push(parse_quote!(#ty: ::std::clone::Clone));
+      }
+      quote!(self.#member = ::std::clone::Clone::clone(&source.#from);)
+    } else {
+      if needs_bound {
+        generics.make_where_clause().predicates
+          .push(parse_quote!(#ty: ::lifeguard::InitializeWith<&'__source #from_type>));
+      }
+      quote!(::lifeguard::InitializeWith::initialize_with(&mut self.#member, &source.#from);)
+    });
+  }
+
+  let name = &input.ident;
+  let (impl_generics, _, where_clause) = generics.split_for_impl();
+  let (_, ty_generics, _) = input.generics.split_for_impl();
+  Ok(quote! {
+    impl #impl_generics ::lifeguard::InitializeWith<&'__source #source> for #name #ty_generics #where_clause {
+      #[inline]
+      fn initialize_with(&mut self, source: &'__source #source) {
+        #(#statements)*
+      }
+    }
+  })
+}
+
+/// Generates `InitializeWith<(A, B, ..)>`, initializing each field from the tuple
+/// element in the same position.
+fn from_tuple(input: &DeriveInput, targets: &[Target]) -> Result<TokenStream> {
+  let mut generics = input.generics.clone();
+  let mut elements = Vec::new();
+  let mut bindings = Vec::new();
+  let mut statements = Vec::new();
+  for (index, target) in targets.iter().enumerate() {
+    let member = &target.member;
+    let binding = Ident::new(&format!("__element{}", index), Span::call_site());
+    if target.options.clone {
+      elements.push(target.ty.clone());
+      statements.push(quote!(self.#member = #binding;));
+    } else {
+      let param = Ident::new(&format!("__Element{}", index), Span::call_site());
+      let ty = target.ty;
+      generics.params.push(parse_quote!(#param));
+      generics.make_where_clause().predicates.push(parse_quote!(#ty: ::lifeguard::InitializeWith<#param>));
+      elements.push(parse_quote!(#param));
+      statements.push(quote!(::lifeguard::InitializeWith::initialize_with(&mut self.#member, #binding);));
+    }
+    bindings.push(binding);
+  }
+
+  let name = &input.ident;
+  let (impl_generics, _, where_clause) = generics.split_for_impl();
+  let (_, ty_generics, _) = input.generics.split_for_impl();
+  Ok(quote! {
+    impl #impl_generics ::lifeguard::InitializeWith<(#(#elements,)*)> for #name #ty_generics #where_clause {
+      #[inline]
+      fn initialize_with(&mut self, source: (#(#elements,)*)) {
+        let (#(#bindings,)*) = source;
+        #(#statements)*
+      }
+    }
+  })
+}
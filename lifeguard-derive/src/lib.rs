@@ -0,0 +1,68 @@
+//! Derive macros for the traits in the `lifeguard` crate. Enable them with
+//! `lifeguard`'s `derive` feature rather than depending on this crate directly.
+extern crate proc_macro;
+extern crate proc_macro2;
+#[macro_use]
+extern crate quote;
+#[macro_use]
+extern crate syn;
+
+use proc_macro::TokenStream;
+use syn::DeriveInput;
+
+/// Turns `error` into `compile_error!` invocations. `syn::Error::into_compile_error`
+/// refers to `::core`, which isn't in scope in crates using the 2015 edition.
+fn compile_errors(error: syn::Error) -> proc_macro2::TokenStream {
+  error.into_iter()
+    .map(|error| {
+      let message = error.to_string();
+      quote_spanned!(error.span()=> compile_error!(#message);)
+    })
+    .collect()
+}
+
+mod attributes;
+mod initialize_with;
+mod recycleable;
+
+/// Implements `Recycleable` for a struct by delegating `new()` and `reset()` to
+/// each of its fields. Fields that aren't `Recycleable` themselves can be
+/// configured with a `#[recycle(...)]` attribute:
+///
+/// * `#[recycle(skip)]` creates the field with `Default::default()` and leaves it
+///   untouched on reset.
+/// * `#[recycle(default = "expr")]` sets the field to `expr` on creation and on reset.
+/// * `#[recycle(reset_with = "path")]` resets the field by calling `path(&mut field)`.
+///   Unless a `default` is also given, the field is created with `Default::default()`.
+#[proc_macro_derive(Recycleable, attributes(recycle))]
+pub fn derive_recycleable(input: TokenStream) -> TokenStream {
+  let input = parse_macro_input!(input as DeriveInput);
+  recycleable::derive(&input)
+    .unwrap_or_else(compile_errors)
+    .into()
+}
+
+/// Implements `InitializeWith` for a struct by initializing each of its fields
+/// from a source value. The sources are listed in attributes on the struct:
+///
+/// * `#[initialize_with(source = "Type")]` implements `InitializeWith<&Type>`. Each
+///   field is initialized from the field of `Type` with the same name.
+/// * `#[initialize_with(tuple)]` implements `InitializeWith<(A, B, ..)>`, with one
+///   tuple element per field, in declaration order.
+///
+/// Fields are initialized with their own `InitializeWith` impls. A field's
+/// `#[initialize_with(...)]` attribute can change that:
+///
+/// * `#[initialize_with(skip)]` leaves the field as it was reset.
+/// * `#[initialize_with(from = "name")]` reads the field `name` of a `source` instead.
+/// * `#[initialize_with(from_type = "Type")]` gives the type of the `source` field,
+///   for generic structs whose fields aren't initialized from a field of the same type.
+/// * `#[initialize_with(clone)]` assigns a clone of the source field, for fields like
+///   integers that don't implement `InitializeWith`. From a tuple, the element is moved.
+#[proc_macro_derive(InitializeWith, attributes(initialize_with))]
+pub fn derive_initialize_with(input: TokenStream) -> TokenStream {
+  let input = parse_macro_input!(input as DeriveInput);
+  initialize_with::derive(&input)
+    .unwrap_or_else(compile_errors)
+    .into()
+}
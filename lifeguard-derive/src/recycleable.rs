@@ -0,0 +1,61 @@
+use proc_macro2::TokenStream;
+use syn::{Data, DeriveInput, Error, Member, Result};
+
+use attributes::RecycleOptions;
+
+pub fn derive(input: &DeriveInput) -> Result<TokenStream> {
+  let data = match input.data {
+    Data::Struct(ref data) => data,
+    _ => return Err(Error::new_spanned(&input.ident, "`Recycleable` can only be derived for structs"))
+  };
+
+  let mut generics = input.generics.clone();
+  let mut initializers = Vec::new();
+  let mut resets = Vec::new();
+  for (index, field) in data.fields.iter().enumerate() {
+    let member = match field.ident {
+      Some(ref ident) => Member::Named(ident.clone()),
+      None => Member::Unnamed(index.into())
+    };
+    let ty = &field.ty;
+    let options = RecycleOptions::of(field)?;
+
+    let initializer = match options.default {
+      Some(ref expr) => quote!(#expr),
+      None if options.skip || options.reset_with.is_some() => quote!(::std::default::Default::default()),
+      None => {
+        generics.make_where_clause().predicates.push(parse_quote!(#ty: ::lifeguard::Recycleable));
+        quote!(<#ty as ::lifeguard::Recycleable>::new())
+      }
+    };
+    initializers.push(quote!(#member: #initializer));
+
+    if options.skip {
+      continue;
+    }
+    resets.push(match (options.reset_with, options.default) {
+      (Some(path), _) => quote!(#path(&mut self.#member);),
+      (None, Some(expr)) => quote!(self.#member = #expr;),
+      (None, None) => quote!(::lifeguard::Recycleable::reset(&mut self.#member);)
+    });
+  }
+
+  let name = &input.ident;
+  let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+  Ok(quote! {
+    impl #impl_generics ::lifeguard::Recycleable for #name #ty_generics #where_clause {
+      #[inline]
+      fn new() -> Self {
+        #name {
+          #(#initializers,)*
+        }
+      }
+
+      #[inline]
+      #[allow(unused_variables)]
+      fn reset(&mut self) {
+        #(#resets)*
+      }
+    }
+  })
+}
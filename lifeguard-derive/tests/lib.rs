@@ -0,0 +1,135 @@
+extern crate lifeguard;
+
+#[cfg(test)]
+mod tests {
+  use lifeguard::*;
+
+  fn record_reset(events: &mut Vec<String>) {
+    events.clear();
+    events.push("reset".to_owned());
+  }
+
+  #[derive(Recycleable)]
+  struct Message {
+    topic: String,
+    payload: Vec<u8>,
+    #[recycle(default = "3")]
+    retries: u32,
+    #[recycle(skip)]
+    id: u64,
+    #[recycle(reset_with = "record_reset")]
+    events: Vec<String>
+  }
+
+  #[derive(Recycleable)]
+  struct Pair<T>(T, String);
+
+  #[test]
+  fn test_derive_recycleable() {
+    let pool : Pool<Message> = Pool::with_size(1);
+    let mut message = pool.new();
+    assert_eq!(3, message.retries);
+    assert_eq!(0, message.id);
+    message.topic.push_str("orders");
+    message.payload.extend_from_slice(b"abc");
+    message.retries = 0;
+    message.id = 7;
+    message.events.push("sent".to_owned());
+    drop(message);
+
+    let message = pool.new();
+    assert_eq!("", message.topic);
+    assert!(message.payload.is_empty());
+    assert_eq!(3, message.retries);
+    assert_eq!(7, message.id);
+    assert_eq!(vec!["reset".to_owned()], message.events);
+  }
+
+  #[test]
+  fn test_derive_recycleable_generic_tuple_struct() {
+    let pool : Pool<Pair<Vec<u8>>> = Pool::with_size(1);
+    {
+      let mut pair = pool.new();
+      pair.0.push(1);
+      pair.1.push_str("one");
+    }
+    let pair = pool.new();
+    assert!(pair.0.is_empty());
+    assert!(pair.1.is_empty());
+  }
+
+  struct Request {
+    topic: String,
+    body: String,
+    attempt: u32
+  }
+
+  #[derive(Recycleable, InitializeWith)]
+  #[initialize_with(source = "Request")]
+  #[initialize_with(tuple)]
+  struct Envelope {
+    topic: String,
+    #[initialize_with(from = "body")]
+    payload: String,
+    #[recycle(default = "0")]
+    #[initialize_with(clone)]
+    attempt: u32,
+    #[initialize_with(skip)]
+    log: Vec<String>
+  }
+
+  #[test]
+  fn test_derive_initialize_with_reference() {
+    let pool : Pool<Envelope> = Pool::with_size(1);
+    let request = Request { topic: "orders".to_owned(), body: "abc".to_owned(), attempt: 2 };
+    let envelope = pool.new_from(&request);
+    assert_eq!("orders", envelope.topic);
+    assert_eq!("abc", envelope.payload);
+    assert_eq!(2, envelope.attempt);
+    assert!(envelope.log.is_empty());
+  }
+
+  #[test]
+  fn test_derive_initialize_with_tuple() {
+    let pool : Pool<Envelope> = Pool::with_size(1);
+    let envelope = pool.new_from(("orders", "abc", 5));
+    assert_eq!("orders", envelope.topic);
+    assert_eq!("abc", envelope.payload);
+    assert_eq!(5, envelope.attempt);
+  }
+
+  struct Labelled<T> {
+    label: &'static str,
+    value: T,
+    count: usize
+  }
+
+  #[derive(Recycleable, InitializeWith)]
+  #[initialize_with(source = "Labelled<T>")]
+  #[initialize_with(tuple)]
+  struct Slot<T> {
+    #[initialize_with(from = "label", from_type = "&'static str")]
+    name: T,
+    value: T,
+    #[recycle(skip)]
+    #[initialize_with(clone)]
+    count: usize
+  }
+
+  #[test]
+  fn test_derive_initialize_with_generic_struct() {
+    let pool : Pool<Slot<String>> = Pool::with_size(1);
+    let labelled = Labelled { label: "orders", value: "abc".to_owned(), count: 2 };
+    let slot = pool.new_from(&labelled);
+    assert_eq!("orders", slot.name);
+    assert_eq!("abc", slot.value);
+    assert_eq!(2, slot.count);
+    drop(slot);
+
+    let pool : Pool<Slot<Vec<u8>>> = Pool::with_size(1);
+    let slot = pool.new_from((b"ab".iter().cloned(), b"cd".iter().cloned(), 3));
+    assert_eq!(b"ab", &slot.name[..]);
+    assert_eq!(b"cd", &slot.value[..]);
+    assert_eq!(3, slot.count);
+  }
+}
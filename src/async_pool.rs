@@ -0,0 +1,358 @@
+use std::fmt;
+use std::future::Future;
+use std::ops::{Deref, DerefMut};
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+use std::pin::Pin;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, OnceLock};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use ::{InitializeWith, OptionSetter, PoolError, Recycleable, RecycledInner, Supply};
+use slots::Slots;
+
+/// A smartpointer which uses atomic reference counting (`Arc`) to know
+/// when to move its wrapped value back to the `AsyncPool` that issued it.
+/// Dropping it frees up a slot for the next task waiting in `acquire`.
+///
+/// Cloning an `AsyncRecycled` checks out an additional value even if the
+/// pool's limit on outstanding values has already been reached, and without
+/// waiting in line behind pending `acquire` calls. The clone counts toward the
+/// limit until it's returned.
+pub struct AsyncRecycled<T> where T: Recycleable + Send {
+  value: RecycledInner<Arc<AsyncCollection<T>>, T>
+}
+
+impl_recycled!{ AsyncRecycled, AsyncRecycled<T>, Arc<AsyncCollection<T>>, Recycleable + Send }
+
+/// The storage shared by an `AsyncPool`, its smartpointers and its pending futures.
+/// Waiting tasks are woken when a slot is handed to them.
+type AsyncCollection<T> = Slots<T, Waker>;
+
+/// A thread-safe pool that limits how many of its values can be checked out at once.
+///
+/// Once `MaxOutstanding` values are in use, `AsyncPool::acquire` returns a future that
+/// resolves when one of them is returned. Waiting tasks are served in the order in which
+/// they started waiting. `AsyncPool` does not depend on any particular async runtime.
+pub struct AsyncPool<T> where T: Recycleable + Send {
+  values: Arc<AsyncCollection<T>>
+}
+
+impl <T> AsyncPool<T> where T: Recycleable + Send {
+
+  /// Returns the number of values remaining in the pool.
+  #[inline]
+  pub fn size(&self) -> usize {
+    self.values.len()
+  }
+
+  /// Returns the maximum number of values the pool can hold.
+  #[inline]
+  pub fn max_size(&self) -> usize {
+    self.values.cap()
+  }
+
+  /// Returns the number of values that are currently checked out.
+  #[inline]
+  pub fn outstanding(&self) -> usize {
+    self.values.outstanding()
+  }
+
+  /// Returns the maximum number of values that can be checked out at once.
+  #[inline]
+  pub fn max_outstanding(&self) -> usize {
+    self.values.max_outstanding()
+  }
+
+  /// Returns a future which resolves to a value from the pool wrapped in an
+  /// `AsyncRecycled` smartpointer. If the limit on outstanding values has been
+  /// reached, the future waits until another value is returned.
+  ///
+  /// Dropping the future before it resolves gives up its place in line.
+  #[inline]
+  pub fn acquire(&self) -> Acquire<T> {
+    Acquire { waiting: Waiting::new(self.values.clone(), None) }
+  }
+
+  /// Like `acquire`, but the returned future resolves to `Err(PoolError::TimedOut)`
+  /// if no value becomes available within `timeout`. A timeout too long to be
+  /// represented, like `Duration::MAX`, never elapses.
+  #[inline]
+  pub fn acquire_timeout(&self, timeout: Duration) -> AcquireTimeout<T> {
+    let deadline = Instant::now().checked_add(timeout);
+    AcquireTimeout { waiting: Waiting::new(self.values.clone(), deadline) }
+  }
+
+  /// Removes a value from the pool and returns it wrapped in an `AsyncRecycled`
+  /// smartpointer without waiting. Returns `Err(PoolError::Exhausted)` if the
+  /// limit on outstanding values has been reached or other tasks are waiting.
+  #[inline]
+  pub fn try_acquire(&self) -> Result<AsyncRecycled<T>, PoolError> {
+    let t = self.try_claim()?;
+    Ok(AsyncRecycled::new(self.values.clone(), t))
+  }
+
+  /// Like `try_acquire`, but initializes the value using the provided source value.
+  #[inline]
+  pub fn try_acquire_from<A>(&self, source: A) -> Result<AsyncRecycled<T>, PoolError> where T: InitializeWith<A> {
+    let t = self.try_claim()?;
+    Ok(AsyncRecycled::new_from(self.values.clone(), t, source))
+  }
+
+  fn try_claim(&self) -> Result<T, PoolError> {
+    if !self.values.try_take_slot() {
+      return Err(PoolError::Exhausted);
+    }
+    Ok(self.values.fill_slot())
+  }
+
+  /// Associates the provided value with the pool by wrapping it in an
+  /// `AsyncRecycled` smartpointer. The value counts towards the limit on
+  /// outstanding values even if that limit has already been reached.
+  #[inline]
+  pub fn attach(&self, value: T) -> AsyncRecycled<T> {
+    self.values.take_extra_slot();
+    AsyncRecycled::new(self.values.clone(), value)
+  }
+}
+
+/// The state shared by `Acquire` and `AcquireTimeout`.
+struct Waiting<T> where T: Recycleable + Send {
+  values: Arc<AsyncCollection<T>>,
+  deadline: Option<Instant>,
+  // Our place in line, once we've had to wait.
+  id: Option<usize>,
+  // Our entry in the timer, once we've had to wait for a deadline.
+  timer: Option<usize>,
+  done: bool
+}
+
+impl <T> Waiting<T> where T: Recycleable + Send {
+  fn new(values: Arc<AsyncCollection<T>>, deadline: Option<Instant>) -> Waiting<T> {
+    Waiting { values, deadline, id: None, timer: None, done: false }
+  }
+
+  fn cancel_timer(&mut self) {
+    if let Some(timer_id) = self.timer.take() {
+      timer().cancel(timer_id);
+    }
+  }
+
+  fn poll_value(&mut self, cx: &mut Context) -> Poll<Result<AsyncRecycled<T>, PoolError>> {
+    assert!(!self.done, "Acquire future polled after completion.");
+    let claimed = match self.id {
+      None => match self.values.take_slot_or_join(cx.waker().clone()) {
+        Some(id) => {
+          self.id = Some(id);
+          false
+        },
+        None => true
+      },
+      Some(id) => self.values.take_granted(id, Some(cx.waker().clone()))
+    };
+    if claimed {
+      let value = self.values.fill_slot();
+      self.cancel_timer();
+      self.done = true;
+      return Poll::Ready(Ok(AsyncRecycled::new(self.values.clone(), value)));
+    }
+    if let Some(deadline) = self.deadline {
+      if Instant::now() >= deadline {
+        // A slot handed over in the meantime is passed along to the next task.
+        if let Some(id) = self.id {
+          self.values.leave(id);
+        }
+        self.cancel_timer();
+        self.done = true;
+        return Poll::Ready(Err(PoolError::TimedOut));
+      }
+      self.timer = Some(timer().wake_at(self.timer, deadline, cx.waker()));
+    }
+    Poll::Pending
+  }
+}
+
+impl <T> Drop for Waiting<T> where T: Recycleable + Send {
+  fn drop(&mut self) {
+    if self.done {
+      return;
+    }
+    self.cancel_timer();
+    // If we'd already been handed a slot, it's passed along to the next task in line.
+    if let Some(id) = self.id {
+      self.values.leave(id);
+    }
+  }
+}
+
+/// A future which resolves to a value from an `AsyncPool`. Created by `AsyncPool::acquire`.
+pub struct Acquire<T> where T: Recycleable + Send {
+  waiting: Waiting<T>
+}
+
+impl <T> Future for Acquire<T> where T: Recycleable + Send {
+  type Output = AsyncRecycled<T>;
+
+  fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<AsyncRecycled<T>> {
+    match self.get_mut().waiting.poll_value(cx) {
+      Poll::Ready(Ok(value)) => Poll::Ready(value),
+      Poll::Ready(Err(_)) => unreachable!("Acquire has no deadline."),
+      Poll::Pending => Poll::Pending
+    }
+  }
+}
+
+/// A future which resolves to a value from an `AsyncPool` or to `PoolError::TimedOut`.
+/// Created by `AsyncPool::acquire_timeout`.
+pub struct AcquireTimeout<T> where T: Recycleable + Send {
+  waiting: Waiting<T>
+}
+
+impl <T> Future for AcquireTimeout<T> where T: Recycleable + Send {
+  type Output = Result<AsyncRecycled<T>, PoolError>;
+
+  fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<AsyncRecycled<T>, PoolError>> {
+    self.get_mut().waiting.poll_value(cx)
+  }
+}
+
+/// Wakes tasks whose `acquire_timeout` deadlines have passed. A single background
+/// thread serves every pool so that timeouts work without an async runtime.
+struct Timer {
+  deadlines: Mutex<Deadlines>,
+  condvar: Condvar
+}
+
+/// The tasks waiting on the timer, each identified by the id `wake_at` gave it.
+struct Deadlines {
+  next_id: usize,
+  entries: Vec<(usize, Instant, Waker)>
+}
+
+fn timer() -> &'static Timer {
+  static TIMER: OnceLock<Timer> = OnceLock::new();
+  TIMER.get_or_init(|| {
+    thread::Builder::new()
+      .name("lifeguard-timer".to_owned())
+      .spawn(|| timer().run())
+      .expect("Failed to spawn the lifeguard timer thread.");
+    let deadlines = Deadlines { next_id: 0, entries: Vec::new() };
+    Timer { deadlines: Mutex::new(deadlines), condvar: Condvar::new() }
+  })
+}
+
+impl Timer {
+  #[inline]
+  fn lock(&self) -> MutexGuard<'_, Deadlines> {
+    self.deadlines.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+  }
+
+  /// Arranges for `waker` to be woken at `deadline` and returns the id of the entry.
+  /// If `existing` still has an entry, only its waker is updated.
+  fn wake_at(&self, existing: Option<usize>, deadline: Instant, waker: &Waker) -> usize {
+    let mut deadlines = self.lock();
+    if let Some(id) = existing {
+      if let Some(entry) = deadlines.entries.iter_mut().find(|entry| entry.0 == id) {
+        if !entry.2.will_wake(waker) {
+          entry.2 = waker.clone();
+        }
+        return id;
+      }
+    }
+    let id = deadlines.next_id;
+    deadlines.next_id = deadlines.next_id.wrapping_add(1);
+    deadlines.entries.push((id, deadline, waker.clone()));
+    self.condvar.notify_one();
+    id
+  }
+
+  /// Forgets the entry with the given id, if the timer hasn't fired it already.
+  fn cancel(&self, id: usize) {
+    let waker = {
+      let mut deadlines = self.lock();
+      let index = deadlines.entries.iter().position(|entry| entry.0 == id);
+      index.map(|index| deadlines.entries.swap_remove(index).2)
+    };
+    // Drop the waker outside of the lock.
+    drop(waker);
+  }
+
+  fn run(&self) {
+    let mut deadlines = self.lock();
+    loop {
+      let now = Instant::now();
+      let mut expired = Vec::new();
+      let mut index = 0;
+      while index < deadlines.entries.len() {
+        if deadlines.entries[index].1 <= now {
+          expired.push(deadlines.entries.swap_remove(index).2);
+        } else {
+          index += 1;
+        }
+      }
+      if !expired.is_empty() {
+        drop(deadlines);
+        for waker in expired {
+          waker.wake();
+        }
+        deadlines = self.lock();
+        continue;
+      }
+      deadlines = match deadlines.entries.iter().map(|&(_, deadline, _)| deadline).min() {
+        Some(next) => self.condvar.wait_timeout(deadlines, next - now)
+          .unwrap_or_else(|poisoned| poisoned.into_inner()).0,
+        None => self.condvar.wait(deadlines).unwrap_or_else(|poisoned| poisoned.into_inner())
+      };
+    }
+  }
+}
+
+/// Produces an `AsyncPoolBuilder` instance
+///
+/// # Example
+///
+/// ```
+/// extern crate lifeguard;
+/// use lifeguard::*;
+///
+/// fn main() {
+///   let pool: AsyncPool<String> = async_pool()
+///     .with(StartingSize(16))
+///     .with(MaxOutstanding(64))
+///     .with(Supplier(|| String::with_capacity(1024)))
+///     .build();
+/// }
+/// ```
+pub fn async_pool<T>() -> AsyncPoolBuilder<T> where T: Recycleable + Send {
+  AsyncPoolBuilder {
+    starting_size: 16,
+    max_size: usize::MAX,
+    max_outstanding: usize::MAX,
+    supplier: None
+  }
+}
+
+/// Used to define settings for and ultimately create an `AsyncPool`.
+pub struct AsyncPoolBuilder<T> where T: Recycleable + Send {
+  pub starting_size: usize,
+  pub max_size: usize,
+  pub max_outstanding: usize,
+  pub supplier: Option<Box<dyn Supply<Output=T> + Send>>,
+}
+
+impl <T> AsyncPoolBuilder<T> where T: Recycleable + Send {
+  pub fn with<U>(self, option_setter: U) -> AsyncPoolBuilder<T> where
+      U: OptionSetter<AsyncPoolBuilder<T>> {
+    option_setter.set_option(self)
+  }
+
+  pub fn build(self) -> AsyncPool<T> {
+    let supplier = self.supplier.unwrap_or(Box::new(|| T::new()));
+    let values: AsyncCollection<T> =
+      Slots::new(supplier, self.starting_size, self.max_size, self.max_outstanding);
+    AsyncPool {
+      values: Arc::new(values)
+    }
+  }
+}
@@ -0,0 +1,91 @@
+use std::any::Any;
+
+use ::{Recycleable, Supply, BoxedTrySupply, ResetFn};
+
+/// Stands in for the `Supplier` or `Resetter` of a `PoolBuilder` that hasn't been
+/// given one. The pool falls back on `T::new` or `T::reset`, so `T` must be
+/// `Recycleable` for the builder to build.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Unset;
+
+/// A boxed `TrySupply` whose error type has been forgotten so that the collection
+/// doesn't need to know it. Errors are boxed, and `Pool` unboxes them again.
+pub type ErasedTrySupply<T> = Box<dyn FnMut() -> Result<T, Box<dyn Any>>>;
+
+/// Where a `PoolCell` gets its new values from. `Recycleable` types that have no
+/// `Supplier` are created through a plain function pointer rather than a box.
+pub enum Source<T> {
+  Function(fn() -> T),
+  Supply(Box<dyn Supply<Output=T>>),
+  TrySupply(ErasedTrySupply<T>)
+}
+
+/// How a `PoolCell` resets returned values. Like `Source`, only a custom
+/// `Resetter` is boxed.
+pub enum Reset<T> {
+  Function(fn(&mut T)),
+  Custom(ResetFn<T>)
+}
+
+impl <T> Reset<T> {
+  #[inline]
+  pub fn reset(&mut self, value: &mut T) {
+    match *self {
+      Reset::Function(reset) => reset(value),
+      Reset::Custom(ref mut reset) => reset(value)
+    }
+  }
+}
+
+/// A pool's source, the function it falls back on when that source is busy, and
+/// its resetter.
+pub type Parts<T> = (Source<T>, Option<fn() -> T>, Reset<T>);
+
+/// The supplier and resetter held by a `PoolBuilder`, as a pair of `Unset`,
+/// `Box<dyn Supply>`, `BoxedTrySupply` or `ResetFn` values. Only the pairs a pool
+/// can be built from implement it: either both are given, or `T` is `Recycleable`.
+pub trait Callbacks<T, E> {
+  fn into_parts(self) -> Parts<T>;
+}
+
+impl <T, E> Callbacks<T, E> for (Unset, Unset) where T: Recycleable {
+  #[inline]
+  fn into_parts(self) -> Parts<T> {
+    (Source::Function(T::new), Some(T::new), Reset::Function(T::reset))
+  }
+}
+
+impl <T, E> Callbacks<T, E> for (Box<dyn Supply<Output=T>>, Unset) where T: Recycleable {
+  #[inline]
+  fn into_parts(self) -> Parts<T> {
+    (Source::Supply(self.0), Some(T::new), Reset::Function(T::reset))
+  }
+}
+
+impl <T, E> Callbacks<T, E> for (BoxedTrySupply<T, E>, Unset) where T: Recycleable {
+  #[inline]
+  fn into_parts(self) -> Parts<T> {
+    (Source::TrySupply(self.0.supplier), Some(T::new), Reset::Function(T::reset))
+  }
+}
+
+impl <T, E> Callbacks<T, E> for (Unset, ResetFn<T>) where T: Recycleable {
+  #[inline]
+  fn into_parts(self) -> Parts<T> {
+    (Source::Function(T::new), Some(T::new), Reset::Custom(self.1))
+  }
+}
+
+impl <T, E> Callbacks<T, E> for (Box<dyn Supply<Output=T>>, ResetFn<T>) {
+  #[inline]
+  fn into_parts(self) -> Parts<T> {
+    (Source::Supply(self.0), None, Reset::Custom(self.1))
+  }
+}
+
+impl <T, E> Callbacks<T, E> for (BoxedTrySupply<T, E>, ResetFn<T>) {
+  #[inline]
+  fn into_parts(self) -> Parts<T> {
+    (Source::TrySupply(self.0.supplier), None, Reset::Custom(self.1))
+  }
+}
@@ -0,0 +1,29 @@
+use std::time::Instant;
+
+/// A source of the current time. Pools with a `MaxIdleTime` use it to stamp
+/// returned values and to decide which of them have been idle too long.
+///
+/// `SystemClock` is used unless another clock is provided with `TimeSource`,
+/// which lets eviction be tested without sleeping. Any `Fn() -> Instant` is a `Clock`.
+pub trait Clock {
+  /// Returns the current time. Successive calls must never go backwards.
+  fn now(&self) -> Instant;
+}
+
+/// A `Clock` that reads the system's monotonic clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+  #[inline]
+  fn now(&self) -> Instant {
+    Instant::now()
+  }
+}
+
+impl <F> Clock for F where F: Fn() -> Instant {
+  #[inline]
+  fn now(&self) -> Instant {
+    self()
+  }
+}
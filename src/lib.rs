@@ -1,15 +1,32 @@
 #[allow(dead_code)]
 use std::rc::Rc;
-use std::cell::RefCell;
+use std::any::Any;
+use std::convert::Infallible;
+use std::marker::PhantomData;
+use std::cell::{Cell, Ref, RefCell, RefMut};
+use std::error;
 use std::fmt;
-use std::ops::{Drop, Deref, DerefMut};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll, Waker};
+use std::panic::{self, AssertUnwindSafe, Location};
+use std::ops::{Drop, Deref, DerefMut, Range};
 use std::convert::{AsRef, AsMut};
 use std::cmp::{Ord, PartialOrd, PartialEq, Eq, Ordering};
 use std::hash::{Hash, Hasher};
-use std::borrow::Borrow;
 use std::collections::VecDeque;
 use std::mem::ManuallyDrop;
 use std::ptr;
+use std::time::{Duration, Instant};
+use std::vec;
+
+#[cfg(feature = "derive")]
+extern crate lifeguard_derive;
+
+/// Derives `Recycleable` and `InitializeWith` for structs whose fields implement them.
+/// Requires the `derive` feature.
+#[cfg(feature = "derive")]
+pub use lifeguard_derive::{Recycleable, InitializeWith};
 
 /// In order to be managed by a `Pool`, values must be of a type that
 /// implements the `Recycleable` trait. This allows the `Pool` to create
@@ -19,6 +36,11 @@ pub trait Recycleable {
   fn new() -> Self;
   /// Sets the state of the modified instance to be that of a freshly
   /// allocated instance, thereby allowing it to be reused.
+  ///
+  /// A value may own smartpointers to other values from the same `Pool`, like the
+  /// children of a tree node, and drop them here. The pool queues them and resets
+  /// them after this value instead of recursing, so tearing down a deep tree doesn't
+  /// grow the stack. See `examples/tree`.
   fn reset(&mut self);
 }
 
@@ -79,40 +101,43 @@ impl <I, T> InitializeWith<I> for Vec<T> where I: Iterator<Item=T>{
 /// A smartpointer which uses a shared reference (`&`) to know
 /// when to move its wrapped value back to the `Pool` that
 /// issued it.
-pub struct Recycled<'a, T: 'a> where T: Recycleable {
-  value: RecycledInner<&'a RefCell<CappedCollection<T>>, T>
+pub struct Recycled<'a, T: 'a> {
+  value: RecycledInner<&'a PoolCell<T>, T>
 }
 
 /// A smartpointer which uses reference counting (`Rc`) to know
 /// when to move its wrapped value back to the `Pool` that
 /// issued it.
-pub struct RcRecycled<T> where T: Recycleable {
-  value: RecycledInner<Rc<RefCell<CappedCollection<T>>>, T>
+pub struct RcRecycled<T> {
+  value: RecycledInner<Rc<PoolCell<T>>, T>
 }
 
 macro_rules! impl_recycled {
   ($name: ident, $typ: ty, $pool: ty) => {
-  impl <'a, T> AsRef<T> for $typ where T : Recycleable {
+    impl_recycled!{ $name, $typ, $pool, Recycleable }
+  };
+  ($name: ident, $typ: ty, $pool: ty, $($bound: tt)+) => {
+  impl <'a, T> AsRef<T> for $typ where T : $($bound)+ {
      /// Gets a shared reference to the value wrapped by the smartpointer.
      fn as_ref(&self) -> &T {
       self.value.as_ref()
     }
   }
 
-  impl <'a, T> AsMut<T> for $typ where T : Recycleable {
+  impl <'a, T> AsMut<T> for $typ where T : $($bound)+ {
      /// Gets a mutable reference to the value wrapped by the smartpointer.
      fn as_mut(&mut self) -> &mut T {
       self.value.as_mut()
     }
   }
 
-  impl <'a, T> fmt::Debug for $typ where T : fmt::Debug + Recycleable {
+  impl <'a, T> fmt::Debug for $typ where T : fmt::Debug + $($bound)+ {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
       self.value.fmt(f)
     }
   }
 
-  impl <'a, T> fmt::Display for $typ where T : fmt::Display + Recycleable {
+  impl <'a, T> fmt::Display for $typ where T : fmt::Display + $($bound)+ {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
       self.value.fmt(f)
     }
@@ -120,35 +145,42 @@ macro_rules! impl_recycled {
 
   //-------- Passthrough trait implementations -----------
 
-  impl <'a, T> PartialEq for $typ where T : PartialEq + Recycleable {
+  impl <'a, T> PartialEq for $typ where T : PartialEq + $($bound)+ {
     fn eq(&self, other: &Self) -> bool {
       self.value.eq(&other.value)
     }
   }
 
-  impl <'a, T> Eq for $typ where T: Eq + Recycleable {}
+  impl <'a, T> Eq for $typ where T: Eq + $($bound)+ {}
 
-  impl <'a, T> PartialOrd for $typ where T: PartialOrd + Recycleable {
+  impl <'a, T> PartialOrd for $typ where T: PartialOrd + $($bound)+ {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
       self.value.partial_cmp(&other.value)
     }
   }
 
-  impl <'a, T> Ord for $typ where T: Ord + Recycleable {
+  impl <'a, T> Ord for $typ where T: Ord + $($bound)+ {
     fn cmp(&self, other: &Self) -> Ordering {
       self.value.cmp(&other.value)
     }
   }
 
-  impl <'a, T> Hash for $typ where T: Hash + Recycleable {
+  impl <'a, T> Hash for $typ where T: Hash + $($bound)+ {
     fn hash<H: Hasher>(&self, state: &mut H) {
       self.value.hash(state)
     }
   }
 
+  impl <'a, T> Clone for $typ where T: Clone + $($bound)+ {
+    #[track_caller]
+    fn clone(&self) -> Self {
+      $name { value: self.value.clone() }
+    }
+  }
+
   //------------------------------------------------------
 
-  impl <'a, T> Deref for $typ where T : Recycleable {
+  impl <'a, T> Deref for $typ where T : $($bound)+ {
     type Target = T;
     #[inline] 
     fn deref(&self) -> &T {
@@ -156,19 +188,21 @@ macro_rules! impl_recycled {
     }
   }
 
-  impl <'a, T> DerefMut for $typ where T : Recycleable {
+  impl <'a, T> DerefMut for $typ where T : $($bound)+ {
     #[inline] 
     fn deref_mut(&mut self) -> &mut T {
       self.as_mut()
     }
   }
 
-  impl <'a, T> $typ where T: Recycleable {
+  impl <'a, T> $typ where T: $($bound)+ {
+    #[track_caller]
     fn new(pool: $pool, value: T) -> $typ {
       $name { value: RecycledInner::new(pool, value) }
     }
     
     #[inline] 
+    #[track_caller]
     fn new_from<A>(pool: $pool, value: T, source: A) -> $typ where T : InitializeWith<A> {
       $name { value: RecycledInner::new_from(pool, value, source) }
     }
@@ -182,94 +216,65 @@ macro_rules! impl_recycled {
   }
 }
 }
-impl_recycled!{ RcRecycled, RcRecycled<T>, Rc<RefCell<CappedCollection<T>>> }
-impl_recycled!{ Recycled, Recycled<'a, T>, &'a RefCell<CappedCollection<T>> }
-
-impl <T> Clone for RcRecycled<T> where T: Clone + Recycleable {
-  fn clone(&self) -> Self {
-    RcRecycled {
-      value: self.value.clone()
-    }
-  }
-}
-
-impl <'a, T> Clone for Recycled<'a, T> where T: Clone + Recycleable {
-  fn clone(&self) -> Self {
-    Recycled {
-      value: self.value.clone()
-    }
-  }
-}
+// A `Pool` can hold any type, so its smartpointers need no bound beyond the implicit `Sized`.
+impl_recycled!{ RcRecycled, RcRecycled<T>, Rc<PoolCell<T>>, Sized }
+impl_recycled!{ Recycled, Recycled<'a, T>, &'a PoolCell<T>, Sized }
 
-struct RecycledInner<P, T> where P: Borrow<RefCell<CappedCollection<T>>>, T : Recycleable {
+struct RecycledInner<P, T> where P: Deref, P::Target: Recycler<T> {
   value: ManuallyDrop<T>,
-  pool: P
+  pool: P,
+  // Identifies the checkout to the pool, if it keeps track of them.
+  ticket: Option<usize>
 }
 
 // ---------- Passthrough Trait Implementations ------------
 
-impl <P, T> PartialEq for RecycledInner<P, T> where P: Borrow<RefCell<CappedCollection<T>>>,
-                                                    T: PartialEq + Recycleable {
+impl <P, T> PartialEq for RecycledInner<P, T> where P: Deref, P::Target: Recycler<T>,
+                                                    T: PartialEq {
   fn eq(&self, other: &Self) -> bool {
     self.value.eq(&other.value)
   }
 }
 
-impl <P, T> Eq for RecycledInner<P, T> where P: Borrow<RefCell<CappedCollection<T>>>,
-                                             T: Eq + Recycleable {
+impl <P, T> Eq for RecycledInner<P, T> where P: Deref, P::Target: Recycler<T>,
+                                             T: Eq {
 
 }
 
-impl <P, T> PartialOrd for RecycledInner<P, T> where P: Borrow<RefCell<CappedCollection<T>>>,
-                                                     T: PartialOrd + Recycleable {
+impl <P, T> PartialOrd for RecycledInner<P, T> where P: Deref, P::Target: Recycler<T>,
+                                                     T: PartialOrd {
   fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
     self.value.partial_cmp(&other.value)
   }
 }
 
-impl <P, T> Ord for RecycledInner<P, T> where P: Borrow<RefCell<CappedCollection<T>>>,
-                                              T: Ord + Recycleable {
+impl <P, T> Ord for RecycledInner<P, T> where P: Deref, P::Target: Recycler<T>,
+                                              T: Ord {
   fn cmp(&self, other: &Self) -> Ordering {
     self.value.cmp(&other.value)
   }
 }
 
-impl <P, T> Hash for RecycledInner<P, T> where P: Borrow<RefCell<CappedCollection<T>>>,
-                                               T: Hash + Recycleable {
+impl <P, T> Hash for RecycledInner<P, T> where P: Deref, P::Target: Recycler<T>,
+                                               T: Hash {
   fn hash<H: Hasher>(&self, state: &mut H) {
     self.value.hash(state)
   }
 }
 
-// Implementing Clone requires duplicating our shared reference to the capped collection, so we have
-// to provide separate implementations for RecycledInners used in Recycled and RcRecycled values.
-impl <'a, T> Clone for RecycledInner<&'a RefCell<CappedCollection<T>>, T> where T: Clone + Recycleable {
-  fn clone(&self) -> Self {
-    let pool_ref = &*self.pool;
-    let mut cloned_value = pool_ref.borrow_mut().remove_or_create();
-    cloned_value.clone_from(&self.value);
-    RecycledInner {
-      value: ManuallyDrop::new(cloned_value),
-      pool: pool_ref
-    }
-  }
-}
-
-impl <T> Clone for RecycledInner<Rc<RefCell<CappedCollection<T>>>, T> where T: Clone + Recycleable {
+impl <P, T> Clone for RecycledInner<P, T> where P: Clone + Deref, P::Target: Recycler<T>, T: Clone {
+  #[track_caller]
   fn clone(&self) -> Self {
     let pool_ref = self.pool.clone();
-    let mut cloned_value = pool_ref.borrow_mut().remove_or_create();
+    let mut cloned_value = pool_ref.remove_for_clone();
     cloned_value.clone_from(&self.value);
-    RecycledInner {
-      value: ManuallyDrop::new(cloned_value),
-      pool: pool_ref
-    }
+    RecycledInner::new(pool_ref, cloned_value)
   }
 }
 
 // -------------------------------------------------------------
 
-impl <P, T> Drop for RecycledInner<P, T> where P: Borrow<RefCell<CappedCollection<T>>>, T : Recycleable {
+impl <P, T> Drop for RecycledInner<P, T> where P: Deref, P::Target: Recycler<T> {
   #[inline] 
   fn drop(&mut self) {
     // We need to rescue `self.value` from `self` and then allow `self` to drop normally.
@@ -281,46 +286,41 @@ impl <P, T> Drop for RecycledInner<P, T> where P: Borrow<RefCell<CappedCollectio
     };
 
     // Convert our newly-rescued `ManuallyDrop<T>` into a `T` so it will eventually drop normally.
-    let mut value = ManuallyDrop::into_inner(value);
+    let value = ManuallyDrop::into_inner(value);
 
-    // If there's no room left in the pool, drop the value here.
-    let pool_ref = self.pool.borrow();
-    if pool_ref.borrow().is_full() {
-      drop(value);
-      return;
+    // Hand the value to the pool, which will either reset and keep it or drop it.
+    self.pool.recycle(value);
+    if let Some(ticket) = self.ticket {
+      self.pool.untrack(ticket);
     }
-
-    // Otherwise, reset value and return it to the pool.
-    value.reset();
-    pool_ref.borrow_mut().insert_prepared_value(value);
   }
 }
 
-impl <P, T> AsRef<T> for RecycledInner<P, T> where P: Borrow<RefCell<CappedCollection<T>>>, T : Recycleable {
+impl <P, T> AsRef<T> for RecycledInner<P, T> where P: Deref, P::Target: Recycler<T> {
    fn as_ref(&self) -> &T {
      &self.value
   }
 }
 
-impl <P, T> AsMut<T> for RecycledInner<P, T> where P: Borrow<RefCell<CappedCollection<T>>>, T : Recycleable {
+impl <P, T> AsMut<T> for RecycledInner<P, T> where P: Deref, P::Target: Recycler<T> {
    fn as_mut(&mut self) -> &mut T {
      &mut self.value
   }
 }
 
-impl <P, T> fmt::Debug for RecycledInner<P, T> where P: Borrow<RefCell<CappedCollection<T>>>, T : fmt::Debug + Recycleable {
+impl <P, T> fmt::Debug for RecycledInner<P, T> where P: Deref, P::Target: Recycler<T>, T : fmt::Debug {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     self.value.fmt(f)
   }
 }
 
-impl <P, T> fmt::Display for RecycledInner<P, T> where P: Borrow<RefCell<CappedCollection<T>>>, T : fmt::Display + Recycleable {
+impl <P, T> fmt::Display for RecycledInner<P, T> where P: Deref, P::Target: Recycler<T>, T : fmt::Display {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     self.value.fmt(f)
   }
 }
 
-impl <P, T> Deref for RecycledInner<P, T> where P: Borrow<RefCell<CappedCollection<T>>>, T : Recycleable {
+impl <P, T> Deref for RecycledInner<P, T> where P: Deref, P::Target: Recycler<T> {
   type Target = T;
   #[inline] 
   fn deref(& self) -> &T {
@@ -328,29 +328,30 @@ impl <P, T> Deref for RecycledInner<P, T> where P: Borrow<RefCell<CappedCollecti
   }
 }
 
-impl <P, T> DerefMut for RecycledInner<P, T> where P: Borrow<RefCell<CappedCollection<T>>>, T : Recycleable {
+impl <P, T> DerefMut for RecycledInner<P, T> where P: Deref, P::Target: Recycler<T> {
   #[inline] 
   fn deref_mut(&mut self) -> & mut T {
     self.as_mut()
   }
 }
 
-impl <P, T> RecycledInner<P, T> where P: Borrow<RefCell<CappedCollection<T>>>, T : Recycleable {
+impl <P, T> RecycledInner<P, T> where P: Deref, P::Target: Recycler<T> {
   #[inline] 
+  #[track_caller]
   fn new(pool: P, value: T) -> RecycledInner<P, T> {
+    let ticket = pool.track(Location::caller());
     RecycledInner {
       value: ManuallyDrop::new(value),
-      pool
+      pool,
+      ticket
     }
   }
   
   #[inline] 
+  #[track_caller]
   fn new_from<A>(pool: P, mut value: T, source: A) -> RecycledInner<P, T> where T : InitializeWith<A> {
     value.initialize_with(source);
-    RecycledInner {
-      value: ManuallyDrop::new(value),
-      pool
-    }
+    RecycledInner::new(pool, value)
   }
 
   #[inline]
@@ -365,65 +366,356 @@ impl <P, T> RecycledInner<P, T> where P: Borrow<RefCell<CappedCollection<T>>>, T
       // Make a byte-for-byte copy of `self.pool`
       ptr::read(&self.pool as *const P)
     };
+    let ticket = self.ticket;
     // Forget `self` so it doesn't go through our custom `Drop` implementation
     mem::forget(self);
-    // Allow `pool` to drop normally
+    // Let the pool know the value will not be coming back, then allow `pool` to drop normally
+    if let Some(ticket) = ticket {
+      pool.untrack(ticket);
+    }
+    pool.on_detach();
     drop(pool);
     // Return the only surviving copy of `value`
     ManuallyDrop::into_inner(value)
   }
 }
 
-struct CappedCollection <T> where T: Recycleable {
+/// Implemented by the storage behind each kind of pool so that a smartpointer
+/// can move its value back to the pool that issued it, wherever it lives.
+trait Recycler<T> {
+  /// Resets `value` and stores it if there is room, dropping it otherwise.
+  fn recycle(&self, value: T);
+  /// Removes a value from storage, allocating a new one if none are available.
+  fn remove_or_create(&self) -> T;
+  /// Provides the value for a clone of a smartpointer, which can't fail.
+  #[inline]
+  fn remove_for_clone(&self) -> T {
+    self.remove_or_create()
+  }
+  /// Called when a smartpointer gives up its value instead of returning it.
+  #[inline]
+  fn on_detach(&self) {}
+  /// Called when a smartpointer takes a value checked out at `location`. Returns
+  /// a ticket for `untrack` if the storage keeps track of its checkouts.
+  #[inline]
+  fn track(&self, _location: &'static Location<'static>) -> Option<usize> {
+    None
+  }
+  /// Called with the smartpointer's ticket when it returns or detaches its value.
+  #[inline]
+  fn untrack(&self, _ticket: usize) {}
+}
+
+impl <T> Recycler<T> for PoolCell<T> {
+  #[inline]
+  fn recycle(&self, value: T) {
+    self.returning.borrow_mut().push(value);
+    // Values returned while another is being recycled, like the children of a node
+    // being reset, wait for the outer call to get to them. That keeps callbacks from
+    // being reentered and the stack from growing with the depth of a tree.
+    if self.recycling.get() {
+      return;
+    }
+    self.recycling.set(true);
+    let _recycling = ClearOnDrop(&self.recycling);
+    loop {
+      let next = self.returning.borrow_mut().pop();
+      match next {
+        Some(value) => self.recycle_one(value),
+        None => break
+      }
+    }
+  }
+
+  #[inline]
+  fn remove_or_create(&self) -> T {
+    self.evict_idle();
+    // If the supplier panics, nothing has been checked out.
+    let value = self.remove_or_supply();
+    self.borrow_mut().stats.check_out();
+    value
+  }
+
+  #[inline]
+  fn remove_for_clone(&self) -> T {
+    if !self.borrow().closed {
+      return self.remove_or_create();
+    }
+    // The pool was closed or dropped after the smartpointer being cloned was checked
+    // out, so its clone gets a new value that will be dropped when it's returned.
+    match self.try_create() {
+      Ok(value) => self.adopt(value),
+      Err(error) => error.raise()
+    }
+  }
+
+  #[inline]
+  fn on_detach(&self) {
+    self.borrow_mut().stats.detached(true);
+  }
+
+  #[inline]
+  fn track(&self, location: &'static Location<'static>) -> Option<usize> {
+    self.checked_out.set(self.checked_out.get() + 1);
+    if self.borrow().tracker.is_none() {
+      return Some(0);
+    }
+    // The clock is user code, so it's read before the collection is borrowed.
+    let now = self.clock.now();
+    let ticket = match self.borrow_mut().tracker {
+      Some(ref mut tracker) => tracker.track(location, now),
+      None => 0
+    };
+    Some(ticket)
+  }
+
+  #[inline]
+  fn untrack(&self, ticket: usize) {
+    if let Some(ref mut tracker) = self.borrow_mut().tracker {
+      tracker.untrack(ticket);
+    }
+    let checked_out = self.checked_out.get() - 1;
+    self.checked_out.set(checked_out);
+    if checked_out == 0 {
+      let waiting = mem::take(&mut *self.waiting.borrow_mut());
+      for waker in waiting {
+        waker.wake();
+      }
+    }
+  }
+}
+
+/// Resets `value` with `reset`, catching any panic. Every pool drops a value whose
+/// reset panicked rather than reusing it, since it may be half reset; such a value
+/// is returned as the `Err` so that the pool can account for it first.
+#[inline]
+fn try_reset<T, F>(mut value: T, reset: F) -> Result<T, T> where F: FnOnce(&mut T) {
+  match panic::catch_unwind(AssertUnwindSafe(|| reset(&mut value))) {
+    Ok(()) => Ok(value),
+    Err(_) => Err(value)
+  }
+}
+
+/// Clears a flag when dropped, even if the thread is unwinding.
+struct ClearOnDrop<'a>(&'a Cell<bool>);
+
+impl <'a> Drop for ClearOnDrop<'a> {
+  #[inline]
+  fn drop(&mut self) {
+    self.0.set(false);
+  }
+}
+
+const SUPPLY_FAILED: &str = "the pool's TrySupply failed to create a value";
+const POOL_CLOSED: &str = "the pool has been closed";
+const SUPPLY_REENTERED: &str = "a pool's Supply can't create a value while it is already creating one";
+
+/// Why `PoolCell` couldn't create a new value.
+enum CreateError {
+  /// The `TrySupply` failed; holds its error.
+  Supply(Box<dyn Any>),
+  /// The supplier asked its own pool for a new value, and there's no `T::new` to
+  /// fall back on.
+  Reentered
+}
+
+impl CreateError {
+  /// Panics with a message describing the error, for callers that can't return it.
+  fn raise(self) -> ! {
+    match self {
+      CreateError::Supply(_) => panic!("{}", SUPPLY_FAILED),
+      CreateError::Reentered => panic!("{}", SUPPLY_REENTERED)
+    }
+  }
+}
+
+/// The values held by a `Pool`, along with its settings and counters. The callbacks
+/// that run user code live alongside it in `PoolCell`.
+struct CappedCollection <T> {
   values: Vec<T>,
   cap: usize,
-  supplier: Box<dyn Supply<Output=T>>
+  stats: Counters,
+  validate_on_checkout: bool,
+  capacity_limit: Option<CapacityLimit<T>>,
+  oversize_policy: OversizePolicy,
+  byte_budget: Option<ByteBudget<T>>,
+  retained_bytes: usize,
+  max_idle_time: Option<Duration>,
+  allocation_policy: AllocationPolicy,
+  reset_timing: ResetTiming,
+  // Returned values that haven't been reset yet, when the `ResetTiming` puts it off.
+  dirty: Vec<T>,
+  // When each value in `dirty` was returned, oldest first. Only kept with a `max_idle_time`.
+  dirty_since: Vec<Instant>,
+  tracker: Option<CheckoutTracker>,
+  return_to_dropped_pool: ReturnToDroppedPool,
+  // Set once returned values should be dropped instead of stored.
+  closed: bool,
+  // When each value in `values` was stored, oldest first. Only kept with a `max_idle_time`.
+  idle_since: Vec<Instant>
 }
 
-impl <T> CappedCollection <T> where T: Recycleable {
+impl <T> CappedCollection <T> {
   #[inline]
-  pub fn new(mut supplier: Box<dyn Supply<Output=T>>, starting_size: usize, max_size: usize) -> CappedCollection<T> {
-    use std::cmp;
-    let starting_size = cmp::min(starting_size, max_size);
-    let values: Vec<T> = 
-      (0..starting_size)
-      .map(|_| supplier.get() )
-      .collect();
+  pub fn new(max_size: usize) -> CappedCollection<T> {
     CappedCollection {
-      values: values,
+      values: Vec::new(),
       cap: max_size,
-      supplier: supplier
+      stats: Counters::new(true),
+      validate_on_checkout: false,
+      capacity_limit: None,
+      oversize_policy: OversizePolicy::Shrink,
+      byte_budget: None,
+      retained_bytes: 0,
+      max_idle_time: None,
+      allocation_policy: AllocationPolicy::Allow,
+      reset_timing: ResetTiming::OnReturn,
+      dirty: Vec::new(),
+      dirty_since: Vec::new(),
+      tracker: None,
+      return_to_dropped_pool: ReturnToDroppedPool::Keep,
+      closed: false,
+      idle_since: Vec::new()
     }
   }
 
   /// Note: This method does not perform a length check.
   /// The provided value must be reset() and there must be room in the pool before this is called.
+  /// `now` is the time it was returned, if the pool has a `max_idle_time`.
   #[inline]
-  pub fn insert_prepared_value(&mut self, value: T) {
+  pub fn insert_prepared_value(&mut self, value: T, now: Option<Instant>) {
+    self.retained_bytes += self.heap_size(&value);
+    if let Some(now) = now {
+      self.idle_since.push(now);
+    }
     self.values.push(value)
   }
 
   #[inline]
   pub fn remove(&mut self) -> Option<T> {
-    self.values.pop()
+    let value = self.values.pop();
+    if let Some(ref value) = value {
+      self.retained_bytes -= self.heap_size(value);
+      self.idle_since.pop();
+    }
+    value
+  }
+
+  /// Like `insert_prepared_value`, but for a value whose reset has been put off.
+  /// It counts against the `MaxRetainedBytes` budget at the size it was returned with.
+  #[inline]
+  pub fn insert_dirty_value(&mut self, value: T, now: Option<Instant>) {
+    self.retained_bytes += self.heap_size(&value);
+    if let Some(now) = now {
+      self.dirty_since.push(now);
+    }
+    self.dirty.push(value)
+  }
+
+  /// Removes the most recently returned value that is still waiting to be reset.
+  #[inline]
+  pub fn remove_dirty(&mut self) -> Option<T> {
+    let value = self.dirty.pop();
+    if let Some(ref value) = value {
+      self.retained_bytes -= self.heap_size(value);
+      self.dirty_since.pop();
+    }
+    value
+  }
+
+  /// Removes every value that had been idle for longer than the `MaxIdleTime` at `now`.
+  pub fn remove_expired(&mut self, now: Instant) -> Vec<T> {
+    let max_idle_time = match self.max_idle_time {
+      Some(max_idle_time) => max_idle_time,
+      None => return Vec::new()
+    };
+    // Values are stored in the order they were returned, so the expired ones are at the front.
+    let count_expired = |stamps: &[Instant]| stamps
+      .iter()
+      .take_while(|&&since| now.saturating_duration_since(since) > max_idle_time)
+      .count();
+    let expired = count_expired(&self.idle_since);
+    let expired_dirty = count_expired(&self.dirty_since);
+    for _ in 0..expired + expired_dirty {
+      self.stats.evicted();
+    }
+    let mut removed = self.drain_dirty(0..expired_dirty);
+    removed.extend(self.remove_oldest(expired));
+    removed
   }
 
+  /// Removes values until at most `len` remain, starting with those still waiting
+  /// to be reset and then the oldest.
+  pub fn remove_excess(&mut self, len: usize) -> Vec<T> {
+    use std::cmp;
+    let excess = self.len().saturating_sub(len);
+    let dirty = cmp::min(excess, self.dirty.len());
+    let split = self.dirty.len() - dirty;
+    let mut removed = self.drain_dirty(split..self.dirty.len());
+    removed.extend(self.remove_oldest(excess - dirty));
+    removed
+  }
+
+  /// Removes the values in `range` of those still waiting to be reset.
+  pub fn drain_dirty(&mut self, range: Range<usize>) -> Vec<T> {
+    if self.max_idle_time.is_some() {
+      self.dirty_since.drain(range.clone());
+    }
+    let removed: Vec<T> = self.dirty.drain(range).collect();
+    for value in &removed {
+      self.retained_bytes -= self.heap_size(value);
+    }
+    removed
+  }
+
+  fn remove_oldest(&mut self, count: usize) -> Vec<T> {
+    if self.max_idle_time.is_some() {
+      self.idle_since.drain(..count);
+    }
+    let removed: Vec<T> = self.values.drain(..count).collect();
+    for value in &removed {
+      self.retained_bytes -= self.heap_size(value);
+    }
+    removed
+  }
+
+  /// Returns the heap bytes held by `value`, or zero if no `MaxRetainedBytes`
+  /// budget was set.
+  #[inline]
+  fn heap_size(&self, value: &T) -> usize {
+    match self.byte_budget {
+      Some(ref budget) => budget.size_of(value),
+      None => 0
+    }
+  }
+
+  /// Returns false if storing `value` would push the idle values past the
+  /// `MaxRetainedBytes` budget.
+  #[inline]
+  fn has_room_for(&self, value: &T) -> bool {
+    match self.byte_budget {
+      Some(ref budget) => self.retained_bytes + budget.size_of(value) <= budget.max(),
+      None => true
+    }
+  }
+
+  /// Returns true if `value` has grown past the `MaxRetainedCapacity`.
   #[inline]
-  pub fn remove_or_create(&mut self) -> T {
-    match self.remove() {
-      Some(value) => value,
-      None => self.supplier.get()
+  fn is_oversized(&self, value: &T) -> bool {
+    match self.capacity_limit {
+      Some(ref limit) => limit.is_exceeded_by(value),
+      None => false
     }
   }
 
   #[inline]
   pub fn is_full(&self) -> bool {
-    self.values.len() >= self.cap
+    self.len() >= self.cap
   }
   
   #[inline]
   pub fn len(&self) -> usize {
-    self.values.len()
+    self.values.len() + self.dirty.len()
   }
   
   #[inline]
@@ -432,179 +724,1307 @@ impl <T> CappedCollection <T> where T: Recycleable {
   }
 }
 
-/// Provides a method which will produce new instances of a type
-pub trait Supply {
-  type Output: Recycleable;
-
-  fn get(&mut self) -> Self::Output;
+/// The storage behind a `Pool`. Each callback that runs user code sits in its own
+/// `RefCell`, and the collection is never borrowed while one runs, so suppliers,
+/// resetters, validators, observers, clocks and destructors may all use the pool themselves.
+struct PoolCell <T> {
+  collection: RefCell<CappedCollection<T>>,
+  source: RefCell<Source<T>>,
+  // Creates values while `source` is busy creating one, if `T` is `Recycleable`.
+  fallback: Option<fn() -> T>,
+  clock: Box<dyn Clock>,
+  resetter: RefCell<Reset<T>>,
+  observer: RefCell<Option<Box<dyn PoolObserver<T>>>>,
+  validator: RefCell<Option<Validator<T>>>,
+  // Values that were returned while another was being recycled.
+  returning: RefCell<Vec<T>>,
+  recycling: Cell<bool>,
+  // The number of smartpointers holding values from this pool, and the tasks
+  // waiting for it to reach zero.
+  checked_out: Cell<usize>,
+  waiting: RefCell<Vec<Waker>>
 }
 
-impl <F, T> Supply for F where F: FnMut() -> T, T: Recycleable {
-  type Output = T;
-  fn get(&mut self) -> T {
-    self()
+impl <T> PoolCell <T> {
+  #[inline]
+  pub fn new(collection: CappedCollection<T>, source: Source<T>, resetter: Reset<T>) -> PoolCell<T> {
+    let fallback = match source {
+      Source::Function(supply) => Some(supply),
+      _ => None
+    };
+    PoolCell {
+      collection: RefCell::new(collection),
+      source: RefCell::new(source),
+      fallback,
+      clock: Box::new(SystemClock),
+      resetter: RefCell::new(resetter),
+      observer: RefCell::new(None),
+      validator: RefCell::new(None),
+      returning: RefCell::new(Vec::new()),
+      recycling: Cell::new(false),
+      checked_out: Cell::new(0),
+      waiting: RefCell::new(Vec::new())
+    }
   }
-}
-
-/// A collection of values that can be reused without requiring new allocations.
-/// 
-/// `Pool` issues each value wrapped in a smartpointer. When the smartpointer goes out of
-/// scope, the wrapped value is automatically returned to the pool.
-pub struct Pool <T> where T : Recycleable {
-  values: Rc<RefCell<CappedCollection<T>>>,
-}
 
-impl <T> Pool <T> where T: Recycleable {
+  #[inline]
+  pub fn borrow(&self) -> Ref<'_, CappedCollection<T>> {
+    self.collection.borrow()
+  }
 
-  /// Creates a pool with `size` elements of type `T` allocated.
   #[inline]
-  pub fn with_size(size: usize) -> Pool <T> {
-    Pool::with_size_and_max(size, usize::MAX)
+  pub fn borrow_mut(&self) -> RefMut<'_, CappedCollection<T>> {
+    self.collection.borrow_mut()
   }
 
-  /// Creates a pool with `size` elements of type `T` allocated
-  /// and sets a maximum pool size of `max_size`. Values being
-  /// added to the pool via `Pool::attach` or being returned to
-  /// the pool upon dropping will instead be discarded if the pool
-  /// is full.
+  /// Asks the supplier for new values until the collection holds `size` of
+  /// them, is full, or has used up its `MaxRetainedBytes` budget.
+  /// Panics if a `TrySupply` fails or the supplier can't be reentered.
   #[inline]
-  pub fn with_size_and_max(starting_size: usize, max_size: usize) -> Pool <T> {
-    let supplier = Box::new(|| T::new());
-    let values: CappedCollection<T> = CappedCollection::new(supplier, starting_size, max_size);
-    Pool {
-      values: Rc::new(RefCell::new(values))
+  pub fn fill(&self, size: usize) {
+    if let Err(error) = self.try_fill(size) {
+      error.raise();
     }
   }
 
-  /// Returns the number of values remaining in the pool.
-  #[inline] 
-  pub fn size(&self) -> usize {
-    (*self.values).borrow().len()
+  /// Like `fill`, but stops at the first error. Does nothing once the pool has been closed.
+  pub fn try_fill(&self, size: usize) -> Result<(), CreateError> {
+    use std::cmp;
+    let size = {
+      let mut collection = self.borrow_mut();
+      if collection.closed {
+        return Ok(());
+      }
+      let size = cmp::min(size, collection.cap);
+      let additional = size.saturating_sub(collection.len());
+      collection.values.reserve(additional);
+      size
+    };
+    while self.borrow().len() < size {
+      let value = self.try_create()?;
+      let now = self.idle_clock();
+      let mut collection = self.borrow_mut();
+      // The supplier may have closed the pool.
+      if collection.closed || collection.is_full() || !collection.has_room_for(&value) {
+        drop(collection);
+        self.discard(value);
+        break;
+      }
+      collection.insert_prepared_value(value, now);
+    }
+    Ok(())
   }
-  
-  /// Returns the maximum number of values the pool can hold.
-  #[inline] 
-  pub fn max_size(&self) -> usize {
-    (*self.values).borrow().cap()
+
+  /// Creates a new value. A supplier that asks its own pool for a new value gets
+  /// one from `T::new` instead, or an error for types that aren't `Recycleable`.
+  #[inline]
+  fn try_create(&self) -> Result<T, CreateError> {
+    let value = match self.source.try_borrow_mut() {
+      Ok(mut source) => match *source {
+        Source::Function(supply) => {
+          // Plain functions keep no state, so they may always be reentered.
+          drop(source);
+          supply()
+        },
+        Source::Supply(ref mut supplier) => supplier.get(),
+        Source::TrySupply(ref mut supplier) => supplier().map_err(CreateError::Supply)?
+      },
+      Err(_) => match self.fallback {
+        Some(supply) => supply(),
+        None => return Err(CreateError::Reentered)
+      }
+    };
+    self.notify(|observer| observer.on_create(&value));
+    Ok(value)
   }
 
-  /// Removes a value from the pool and returns it wrapped in
-  /// a `Recycled smartpointer. If the pool is empty when the
-  /// method is called, a new value will be allocated.
-  #[inline] 
-  pub fn new(&self) -> Recycled<T> {
-    let t = self.detached();
-    Recycled { value: RecycledInner::new(&*self.values, t) }
+  /// Passes the observer, if there is one, to `notify`. Events caused by an
+  /// observer's own callbacks aren't reported back to it.
+  #[inline]
+  fn notify<F>(&self, notify: F) where F: FnOnce(&mut dyn PoolObserver<T>) {
+    if let Ok(mut observer) = self.observer.try_borrow_mut() {
+      if let Some(ref mut observer) = *observer {
+        notify(&mut **observer);
+      }
+    }
   }
 
-  /// Removes a value from the pool, initializes it using the provided
-  /// source value, and returns it wrapped in a `Recycled` smartpointer.
-  /// If the pool is empty when the method is called, a new value will be
-  /// allocated.
-  #[inline(always)] 
-  pub fn new_from<A>(&self, source: A) -> Recycled<T> where T: InitializeWith<A> {
-    let t = self.detached();
-    Recycled { value: RecycledInner::new_from(&*self.values, t, source) }
+  /// Tells the observer that `value` is being dropped, then drops it.
+  #[inline]
+  fn discard(&self, value: T) {
+    self.notify(|observer| observer.on_discard(&value));
+    drop(value);
   }
 
-  /// Associates the provided value with the pool by wrapping it in a
-  /// `Recycled` smartpointer.
-  #[inline] 
-  pub fn attach(&self, value: T) -> Recycled<T> {
-    Recycled { value: RecycledInner::new(&*self.values, value) }
+  /// Returns the time to record a value as idle from, if the pool has a `MaxIdleTime`.
+  #[inline]
+  fn idle_clock(&self) -> Option<Instant> {
+    if self.borrow().max_idle_time.is_some() {
+      Some(self.clock.now())
+    } else {
+      None
+    }
   }
 
-  /// Removes a value from the pool and returns it without wrapping it in
-  /// a smartpointer. When the value goes out of scope it will not be
-  /// returned to the pool.
-  #[inline] 
-  pub fn detached(&self) -> T {
-    let mut collection = self.values.borrow_mut();
-    collection.remove_or_create()
+  /// Consults the `Validate` setting, if any, to decide whether `value` may be reused.
+  /// Values that reach the pool while the validator is running can't be checked, so
+  /// they're treated as unfit.
+  #[inline]
+  fn is_reusable(&self, value: &T) -> bool {
+    let mut validator = match self.validator.try_borrow_mut() {
+      Ok(validator) => validator,
+      Err(_) => return false
+    };
+    match *validator {
+      Some(ref mut validator) => validator(value),
+      None => true
+    }
   }
 
-  /// Removes a value from the pool and returns it wrapped in
-  /// an `RcRecycled` smartpointer. If the pool is empty when the
-  /// method is called, a new value will be allocated.
-  #[inline] 
-  pub fn new_rc(&self) -> RcRecycled<T> {
-    let t = self.detached();
-    let pool_reference = self.values.clone();
-    RcRecycled { value: RecycledInner::new(pool_reference, t) }
+  /// Drops every value that has been idle for longer than the `MaxIdleTime`,
+  /// returning how many were evicted.
+  pub fn evict_idle(&self) -> usize {
+    let now = match self.idle_clock() {
+      Some(now) => now,
+      None => return 0
+    };
+    let expired = self.borrow_mut().remove_expired(now);
+    let count = expired.len();
+    for value in expired {
+      self.discard(value);
+    }
+    count
   }
- 
-  /// Removes a value from the pool, initializes it using the provided
-  /// source value, and returns it wrapped in an `RcRecycled` smartpointer.
-  /// If the pool is empty when the method is called, a new value will be
-  /// allocated.
-  #[inline(always)] 
-  pub fn new_rc_from<A>(&self, source: A) -> RcRecycled<T> where T: InitializeWith<A> {
-    let t = self.detached();
-    let pool_reference = self.values.clone();
-    RcRecycled { value: RecycledInner::new_from(pool_reference, t, source) }
+
+  /// Drops values until at most `len` remain, starting with those still waiting
+  /// to be reset and then the oldest.
+  pub fn truncate(&self, len: usize) {
+    let removed = self.borrow_mut().remove_excess(len);
+    for value in removed {
+      self.discard(value);
+    }
   }
 
-  /// Associates the provided value with the pool by wrapping it in an
-  /// `RcRecycled` smartpointer.
-  #[inline] 
-  pub fn attach_rc(&self, value: T) -> RcRecycled<T> {
-    let pool_reference = self.values.clone();
-    RcRecycled { value: RecycledInner::new(pool_reference, value) }
+  #[inline]
+  pub fn set_cap(&self, cap: usize) {
+    self.borrow_mut().cap = cap;
+    self.truncate(cap);
   }
-}
 
-/// Produces a `PoolBuilder` instance
-/// 
-/// # Example
-/// 
-/// ```
-/// extern crate lifeguard;
-/// use lifeguard::*;
-///
-/// fn main() {
-///   let mut pool: Pool<String> = pool()
-///     .with(StartingSize(128))
-///     .with(MaxSize(4096))
-///     .with(Supplier(|| String::with_capacity(1024)))
-///     .build();
-/// }
-/// ```
-pub fn pool<T>() -> PoolBuilder<T> where T: Recycleable {
-  PoolBuilder {
-    starting_size: 16,
-    max_size: usize::MAX,
-    supplier: None
+  /// Makes checkouts fail and drops values as they are returned.
+  #[inline]
+  pub fn close(&self) {
+    self.borrow_mut().closed = true;
   }
-}
 
-/// Used to define settings for and ultimately create a `Pool`.
-pub struct PoolBuilder<T> where T: Recycleable {
-  pub starting_size: usize,
-  pub max_size: usize,
-  pub supplier: Option<Box<dyn Supply<Output=T>>>,
-}
+  /// Drops every stored value and releases the storage that held them.
+  pub fn release(&self) {
+    self.truncate(0);
+    let mut collection = self.borrow_mut();
+    collection.values.shrink_to_fit();
+    collection.dirty.shrink_to_fit();
+    collection.dirty_since.shrink_to_fit();
+    collection.idle_since.shrink_to_fit();
+  }
+
+  /// Validates, resets and stores a single returned value, or drops it.
+  fn recycle_one(&self, mut value: T) {
+    self.evict_idle();
+    // If there's no room left in the pool, or it's been closed, drop the value here.
+    let full = {
+      let collection = self.borrow();
+      collection.closed || collection.is_full()
+    };
+    if full {
+      self.borrow_mut().stats.discarded(true);
+      self.discard(value);
+      return;
+    }
+
+    // Likewise if the value is no longer fit to be reused.
+    if !self.is_reusable(&value) {
+      self.borrow_mut().stats.rejected(true);
+      self.discard(value);
+      return;
+    }
+
+    // Values whose buffers have grown past `MaxRetainedCapacity` are either dropped or shrunk.
+    let discard_oversized = {
+      let collection = self.borrow();
+      collection.oversize_policy == OversizePolicy::Discard && collection.is_oversized(&value)
+    };
+    if discard_oversized {
+      self.borrow_mut().stats.oversized(true);
+      self.discard(value);
+      return;
+    }
 
-impl <T> PoolBuilder<T> where T: Recycleable {
-  pub fn with<U>(self, option_setter: U) -> PoolBuilder<T> where 
-      U: OptionSetter<PoolBuilder<T>> {
-    option_setter.set_option(self)
+    self.notify(|observer| observer.on_return(&mut value));
+
+    // Otherwise, reset value and return it to the pool, unless the reset has
+    // been put off until later.
+    let deferred = self.borrow().reset_timing != ResetTiming::OnReturn;
+    if deferred {
+      let now = self.idle_clock();
+      let mut collection = self.borrow_mut();
+      if collection.closed || collection.is_full() || !collection.has_room_for(&value) {
+        collection.stats.discarded(true);
+        drop(collection);
+        self.discard(value);
+        return;
+      }
+      collection.stats.returned();
+      collection.insert_dirty_value(value, now);
+      return;
+    }
+    match self.clean(value) {
+      Some(value) => self.store(value, true),
+      None => self.borrow_mut().stats.poisoned(true)
+    }
   }
 
-  pub fn build(self) -> Pool<T> where T: Recycleable {
-    let supplier = self.supplier.unwrap_or(Box::new(|| T::new()));
-    let values: CappedCollection<T> = CappedCollection::new(supplier, self.starting_size, self.max_size);
-    Pool {
-      values: Rc::new(RefCell::new(values))
+  /// Resets a returned value and, if it's still larger than the
+  /// `MaxRetainedCapacity`, shrinks it. Returns `None` if the reset panicked,
+  /// in which case the value has been dropped.
+  #[inline]
+  fn clean(&self, value: T) -> Option<T> {
+    let reset = {
+      let mut resetter = self.resetter.borrow_mut();
+      try_reset(value, |value| resetter.reset(value))
+    };
+    let mut value = match reset {
+      Ok(value) => value,
+      Err(value) => {
+        self.discard(value);
+        return None;
+      }
+    };
+    let mut collection = self.borrow_mut();
+    if collection.is_oversized(&value) {
+      if let Some(ref limit) = collection.capacity_limit {
+        limit.shrink(&mut value);
+      }
+      collection.stats.oversized(false);
     }
+    Some(value)
   }
-}
 
-pub mod settings {
-  use ::{PoolBuilder, Recycleable, Supply};
-    /// Implementing this trait allows a struct to act as a configuration
+  /// Stores a value that has just been reset, or drops it if there is no longer
+  /// room for it. `was_outstanding` is false for values that were already counted
+  /// as returned before their deferred reset.
+  #[inline]
+  fn store(&self, value: T, was_outstanding: bool) {
+    let now = self.idle_clock();
+    let mut collection = self.borrow_mut();
+    if collection.closed || collection.is_full() || !collection.has_room_for(&value) {
+      collection.stats.discarded(was_outstanding);
+      drop(collection);
+      self.discard(value);
+      return;
+    }
+    if was_outstanding {
+      collection.stats.returned();
+    }
+    collection.insert_prepared_value(value, now);
+  }
+
+  /// Resets every value waiting on a deferred reset and stores it for reuse,
+  /// returning how many were reset. Does nothing if called from the resetter itself.
+  pub fn reset_pending(&self) -> usize {
+    if self.resetter.try_borrow_mut().is_err() {
+      return 0;
+    }
+    let mut count = 0;
+    loop {
+      let next = self.borrow_mut().remove_dirty();
+      let value = match next {
+        Some(value) => value,
+        None => break
+      };
+      count += 1;
+      match self.clean(value) {
+        Some(value) => self.store(value, false),
+        None => self.borrow_mut().stats.poisoned(false)
+      }
+    }
+    count
+  }
+
+  /// Removes every stored value, resetting any that are still waiting on a
+  /// deferred reset.
+  pub fn drain(&self) -> Vec<T> {
+    let (mut drained, dirty) = {
+      let mut collection = self.borrow_mut();
+      let count = collection.values.len();
+      let dirty = collection.dirty.len();
+      (collection.remove_oldest(count), collection.drain_dirty(0..dirty))
+    };
+    for value in dirty {
+      match self.clean(value) {
+        Some(value) => drained.push(value),
+        None => self.borrow_mut().stats.poisoned(false)
+      }
+    }
+    let mut collection = self.borrow_mut();
+    for _ in &drained {
+      collection.stats.detached(false);
+    }
+    drained
+  }
+
+  /// Takes the next value to check out. Under `ResetTiming::OnCheckout`, the most
+  /// recently returned values are reset and handed out first, unless the resetter
+  /// is the one checking values out.
+  #[inline]
+  fn remove_next(&self) -> Option<T> {
+    let reset_on_checkout = self.borrow().reset_timing == ResetTiming::OnCheckout;
+    if reset_on_checkout && self.resetter.try_borrow_mut().is_ok() {
+      loop {
+        let next = self.borrow_mut().remove_dirty();
+        let value = match next {
+          Some(value) => value,
+          None => break
+        };
+        match self.clean(value) {
+          Some(value) => return Some(value),
+          None => self.borrow_mut().stats.poisoned(false)
+        }
+      }
+    }
+    self.borrow_mut().remove()
+  }
+
+  #[inline]
+  fn remove_valid(&self) -> Option<T> {
+    while let Some(value) = self.remove_next() {
+      let validate = self.borrow().validate_on_checkout;
+      if !validate || self.is_reusable(&value) {
+        return Some(value);
+      }
+      self.borrow_mut().stats.rejected(false);
+      self.discard(value);
+    }
+    None
+  }
+
+  /// Panics if the pool has been closed, a `TrySupply` fails or the supplier
+  /// can't be reentered.
+  #[inline]
+  pub fn remove_or_supply(&self) -> T {
+    assert!(!self.borrow().closed, "{}", POOL_CLOSED);
+    match self.remove_or_try_create() {
+      Ok(value) => value,
+      Err(error) => error.raise()
+    }
+  }
+
+  pub fn remove_or_try_create(&self) -> Result<T, CreateError> {
+    let mut value = match self.remove_valid() {
+      Some(value) => {
+        self.borrow_mut().stats.hit();
+        value
+      },
+      None => {
+        debug_assert!(self.borrow().allocation_policy == AllocationPolicy::Allow,
+                      "the pool is empty and its AllocationPolicy forbids creating new values");
+        self.borrow_mut().stats.miss();
+        self.try_create()?
+      }
+    };
+    self.notify(|observer| observer.on_checkout(&mut value));
+    Ok(value)
+  }
+
+  /// Like `remove_or_supply`, but returns `None` instead of creating a value.
+  #[inline]
+  pub fn try_remove(&self) -> Option<T> {
+    let mut value = self.remove_valid()?;
+    self.borrow_mut().stats.hit();
+    self.notify(|observer| observer.on_checkout(&mut value));
+    Some(value)
+  }
+
+  /// Counts a value from outside the pool as checked out, as if it had been removed.
+  #[inline]
+  pub fn adopt(&self, mut value: T) -> T {
+    self.borrow_mut().stats.check_out();
+    self.notify(|observer| observer.on_checkout(&mut value));
+    value
+  }
+}
+
+/// Provides a method which will produce new instances of a type
+pub trait Supply {
+  type Output;
+
+  fn get(&mut self) -> Self::Output;
+}
+
+impl <F, T> Supply for F where F: FnMut() -> T {
+  type Output = T;
+  fn get(&mut self) -> T {
+    self()
+  }
+}
+
+/// Provides a method which will attempt to produce new instances of a type,
+/// for values whose creation can fail.
+pub trait TrySupply {
+  type Output;
+  type Error;
+
+  fn try_get(&mut self) -> Result<Self::Output, Self::Error>;
+}
+
+impl <F, T, E> TrySupply for F where F: FnMut() -> Result<T, E> {
+  type Output = T;
+  type Error = E;
+  fn try_get(&mut self) -> Result<T, E> {
+    self()
+  }
+}
+
+/// A `TrySupply` boxed for a `PoolBuilder`, as stored by the `TrySupplier` setting.
+pub struct BoxedTrySupply<T, E> {
+  supplier: ErasedTrySupply<T>,
+  error: PhantomData<fn() -> E>
+}
+
+impl <T, E> BoxedTrySupply<T, E> where E: 'static {
+  pub fn new<S>(mut supplier: S) -> BoxedTrySupply<T, E> where S: TrySupply<Output=T, Error=E> + 'static {
+    BoxedTrySupply {
+      // Boxing the concrete supplier, rather than a `dyn TrySupply`, doesn't require `T: 'static`.
+      supplier: Box::new(move || supplier.try_get().map_err(|error| Box::new(error) as Box<dyn Any>)),
+      error: PhantomData
+    }
+  }
+}
+
+/// Receives notifications as values move through the stages of a `Pool`'s
+/// lifecycle, allowing them to be logged, validated or instrumented. Every
+/// method has an empty default implementation.
+pub trait PoolObserver<T> {
+  /// Called when the `Supply` creates a new value, including values created
+  /// to fill the pool up to its `StartingSize`.
+  #[allow(unused_variables)]
+  fn on_create(&mut self, value: &T) {}
+  /// Called when a value is handed out of the pool, before it is initialized
+  /// by methods like `Pool::new_from`. This includes values taken with
+  /// `Pool::detached`, which are never returned, and values passed to
+  /// `Pool::attach`, which were never in the pool but will be returned to it.
+  #[allow(unused_variables)]
+  fn on_checkout(&mut self, value: &mut T) {}
+  /// Called when a value is returned to the pool, before it is reset.
+  #[allow(unused_variables)]
+  fn on_return(&mut self, value: &mut T) {}
+  /// Called when a value is dropped instead of being kept, either because the pool
+  /// is full or because it failed validation.
+  #[allow(unused_variables)]
+  fn on_discard(&mut self, value: &T) {}
+  /// Called when the `Pool` is dropped while `outstanding` `RcRecycled` values are
+  /// still checked out. `sites` lists where they were checked out, and is empty
+  /// unless `TrackCheckouts` is enabled.
+  #[allow(unused_variables)]
+  fn on_leak(&mut self, outstanding: usize, sites: &[OutstandingCheckouts]) {}
+}
+
+/// A boxed closure, as provided by the `Validate` setting, which decides whether
+/// a value is fit to be reused.
+pub type Validator<T> = Box<dyn FnMut(&T) -> bool>;
+
+/// A boxed closure, as provided by the `Resetter` setting, which returns a value
+/// to a like-new state before it is reused.
+pub type ResetFn<T> = Box<dyn FnMut(&mut T)>;
+
+/// Determines whether a `Pool` may ask its `Supply` for new values once it has
+/// been built and filled up to its `StartingSize`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AllocationPolicy {
+  /// Create a new value whenever the pool is empty.
+  Allow,
+  /// Never create new values after the pool is built. Checking a value out of an
+  /// empty pool with `Pool::try_get` returns `PoolError::Exhausted`, and `Pool::try_new`
+  /// returns `None`. Methods that cannot fail, like `Pool::new`, panic in debug builds
+  /// and allocate anyway in release builds. `Pool::reserve` is an explicit request
+  /// for more values and is not affected.
+  Forbid
+}
+
+/// Determines when a `Pool` resets the values that are returned to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResetTiming {
+  /// Reset each value as soon as it is returned.
+  OnReturn,
+  /// Store returned values as they are and reset each one when it is next checked out.
+  /// Values are still validated on return, but they aren't shrunk to their `MaxRetainedCapacity`
+  /// until they are reset. Until then they count against `MaxRetainedBytes` at the size they
+  /// were returned with, and are evicted after `MaxIdleTime` like reset values.
+  OnCheckout,
+  /// Queue returned values and reset them in a batch when `Pool::reset_pending` is called.
+  /// Queued values can't be checked out, so a pool that is never reset will keep
+  /// creating new values until it's full.
+  Deferred
+}
+
+/// Determines what happens to the values held by `RcRecycled` smartpointers that
+/// are returned after their `Pool` has been dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReturnToDroppedPool {
+  /// Keep the pool's storage and idle values alive until the last outstanding
+  /// value has been returned, storing returned values as usual.
+  Keep,
+  /// Drop the idle values as soon as the `Pool` is dropped, and drop each
+  /// outstanding value as it is returned.
+  Discard
+}
+
+/// Describes why a value could not be checked out of a pool. `E` is the error
+/// type of the pool's `TrySupply`, if it has one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PoolError<E = Infallible> {
+  /// The pool's limit on outstanding values has been reached, or the pool is
+  /// empty and its `AllocationPolicy` forbids creating new values.
+  Exhausted,
+  /// No value became available before the timeout elapsed.
+  TimedOut,
+  /// The pool was empty and its `TrySupply` failed to create a new value.
+  Supply(E),
+  /// The pool has been closed with `Pool::close`.
+  Closed,
+  /// The pool's `Supply` or `TrySupply` asked the same pool for a new value, which
+  /// it can't create while it's busy. Pools of `Recycleable` values use `T::new`
+  /// instead, so only pools given both a `Supplier` and a `Resetter` return this.
+  Reentered
+}
+
+impl <E> PoolError<E> where E: 'static {
+  /// Converts an error from a `PoolCell`, unboxing the `TrySupply`'s error.
+  fn from_supply(error: CreateError) -> PoolError<E> {
+    let error = match error {
+      CreateError::Supply(error) => error,
+      CreateError::Reentered => return PoolError::Reentered
+    };
+    match error.downcast::<E>() {
+      Ok(error) => PoolError::Supply(*error),
+      Err(_) => unreachable!("a pool's TrySupply errors all have the same type")
+    }
+  }
+}
+
+impl <E> fmt::Display for PoolError<E> where E: fmt::Display {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match *self {
+      PoolError::Exhausted => f.write_str("the pool has no value available to check out"),
+      PoolError::TimedOut => f.write_str("timed out waiting for a value to be returned to the pool"),
+      PoolError::Supply(ref error) => write!(f, "failed to create a new value: {}", error),
+      PoolError::Closed => f.write_str(POOL_CLOSED),
+      PoolError::Reentered => f.write_str(SUPPLY_REENTERED)
+    }
+  }
+}
+
+impl <E> error::Error for PoolError<E> where E: error::Error + 'static {
+  fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+    match *self {
+      PoolError::Supply(ref error) => Some(error),
+      _ => None
+    }
+  }
+}
+
+/// A collection of values that can be reused without requiring new allocations.
+/// 
+/// `Pool` issues each value wrapped in a smartpointer. When the smartpointer goes out of
+/// scope, the wrapped value is automatically returned to the pool.
+///
+/// `E` is the error type of the pool's `TrySupply`. Pools built with an infallible
+/// `Supply` use `Infallible`.
+///
+/// If resetting a returned value panics, the panic is caught and the value is dropped
+/// and counted in `PoolStats::poisoned`, so dropping a smartpointer never panics while
+/// unwinding. If the `Supply` panics while creating a value, the panic reaches the
+/// caller and the pool is left usable, with nothing checked out.
+///
+/// Callbacks may use the pool they belong to. Values that a `Supply` asks its own pool
+/// to create are made with `T::new`; pools given both a `Supplier` and a `Resetter`
+/// don't use `T::new`, so `try_get` returns `PoolError::Reentered` and `new` panics instead.
+/// Values returned while the `Validate` callback is running are treated as unfit.
+pub struct Pool <T, E = Infallible> {
+  values: Rc<PoolCell<T>>,
+  error: PhantomData<fn() -> E>
+}
+
+impl <T> Pool <T> where T: Recycleable {
+
+  /// Creates a pool with `size` elements of type `T` allocated.
+  #[inline]
+  pub fn with_size(size: usize) -> Pool <T> {
+    Pool::with_size_and_max(size, usize::MAX)
+  }
+
+  /// Creates a pool with `size` elements of type `T` allocated
+  /// and sets a maximum pool size of `max_size`. Values being
+  /// added to the pool via `Pool::attach` or being returned to
+  /// the pool upon dropping will instead be discarded if the pool
+  /// is full.
+  #[inline]
+  pub fn with_size_and_max(starting_size: usize, max_size: usize) -> Pool <T> {
+    let source = Source::Function(T::new);
+    let resetter = Reset::Function(T::reset);
+    let values = PoolCell::new(CappedCollection::new(max_size), source, resetter);
+    values.fill(starting_size);
+    Pool {
+      values: Rc::new(values),
+      error: PhantomData
+    }
+  }
+}
+
+impl <T, E> Pool <T, E> {
+
+  /// Returns the number of values remaining in the pool, including any that
+  /// are waiting to be reset.
+  #[inline] 
+  pub fn size(&self) -> usize {
+    (*self.values).borrow().len()
+  }
+  
+  /// Returns the maximum number of values the pool can hold.
+  #[inline] 
+  pub fn max_size(&self) -> usize {
+    (*self.values).borrow().cap()
+  }
+
+  /// Returns a snapshot of the pool's counters. See `PoolStats`.
+  #[inline]
+  pub fn stats(&self) -> PoolStats {
+    let collection = (*self.values).borrow();
+    collection.stats.snapshot(collection.dirty.len())
+  }
+
+  /// Lists the smartpointers that have been checked out for at least `older_than`,
+  /// grouped by the call site that checked them out, with the busiest sites first.
+  /// Always empty unless `TrackCheckouts` is enabled.
+  /// Values taken with `Pool::detached` or `detach` are no longer tracked.
+  pub fn outstanding_report(&self, older_than: Duration) -> Vec<OutstandingCheckouts> {
+    if (*self.values).borrow().tracker.is_none() {
+      return Vec::new();
+    }
+    let now = self.values.clock.now();
+    match (*self.values).borrow().tracker {
+      Some(ref tracker) => tracker.report(now, older_than),
+      None => Vec::new()
+    }
+  }
+
+  /// Returns the number of heap bytes held by the values remaining in the pool,
+  /// including those waiting to be reset. Only tracked when a `MaxRetainedBytes` budget was set; otherwise zero.
+  #[inline]
+  pub fn retained_bytes(&self) -> usize {
+    (*self.values).borrow().retained_bytes
+  }
+
+  /// Returns the `MaxRetainedBytes` budget, if one was set.
+  #[inline]
+  pub fn max_retained_bytes(&self) -> Option<usize> {
+    (*self.values).borrow().byte_budget.as_ref().map(ByteBudget::max)
+  }
+
+  /// Changes the maximum number of values the pool can hold. If more than `max_size`
+  /// values remain in the pool, the oldest are dropped. Outstanding values are unaffected
+  /// and will be dropped on return if the pool is full.
+  #[inline]
+  pub fn set_max_size(&self, max_size: usize) {
+    self.values.set_cap(max_size)
+  }
+
+  /// Drops the oldest values remaining in the pool until at most `size` are left.
+  #[inline]
+  pub fn shrink_to(&self, size: usize) {
+    self.values.truncate(size)
+  }
+
+  /// Asks the supplier for `additional` values and adds them to the pool, stopping
+  /// early if the pool fills up or runs out of its `MaxRetainedBytes` budget. Like
+  /// the `StartingSize` prefill, this ignores `AllocationPolicy::Forbid`. Does nothing
+  /// once the pool has been closed.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the pool's `TrySupply` fails. Use `Pool::try_reserve` to handle the error.
+  #[inline]
+  pub fn reserve(&self, additional: usize) {
+    let size = self.values.borrow().len().saturating_add(additional);
+    self.values.fill(size)
+  }
+
+  /// Like `reserve`, but returns the first error from the pool's `TrySupply`. The
+  /// values created before the error are kept.
+  #[inline]
+  pub fn try_reserve(&self, additional: usize) -> Result<(), PoolError<E>> where E: 'static {
+    let size = self.values.borrow().len().saturating_add(additional);
+    self.values.try_fill(size).map_err(PoolError::from_supply)
+  }
+
+  /// Drops every value remaining in the pool.
+  #[inline]
+  pub fn clear(&self) {
+    self.values.truncate(0)
+  }
+
+  /// Resets the values that were returned to the pool under `ResetTiming::Deferred`
+  /// (or `OnCheckout`) and haven't been reset yet, making them available to be
+  /// checked out. Returns how many were reset.
+  #[inline]
+  pub fn reset_pending(&self) -> usize {
+    self.values.reset_pending()
+  }
+
+  /// Closes the pool for shutdown. From now on, `try_get` and `try_get_from` fail with
+  /// `PoolError::Closed`, `try_new` and `try_new_from` return `None`, and the other
+  /// checkout methods panic. Values returned to the pool are dropped instead of stored,
+  /// and `reserve` and `try_reserve` no longer create any. The values already in the pool stay there until they are drained or cleared.
+  /// Smartpointers checked out earlier can still be cloned; each clone gets a new value
+  /// from the `Supply`, which is dropped when it's returned.
+  #[inline]
+  pub fn close(&self) {
+    self.values.close()
+  }
+
+  /// Returns true once `close` has been called.
+  #[inline]
+  pub fn is_closed(&self) -> bool {
+    (*self.values).borrow().closed
+  }
+
+  /// Removes every value remaining in the pool and returns them, resetting any that
+  /// are still waiting on a deferred reset. Drained values are counted as detached.
+  #[inline]
+  pub fn drain(&self) -> vec::IntoIter<T> {
+    self.values.drain().into_iter()
+  }
+
+  /// Closes the pool and returns the values remaining in it. `RcRecycled` values
+  /// that are still checked out are dropped when they are returned.
+  pub fn into_values(self) -> Vec<T> {
+    self.values.close();
+    self.values.drain()
+  }
+
+  /// Returns a future that resolves once every smartpointer checked out of the pool
+  /// has been returned or detached. A `Pool` belongs to a single thread, so there is
+  /// no blocking version: the values can only come back while the caller yields.
+  #[inline]
+  pub fn wait_until_all_returned(&self) -> AllReturned<'_, T> {
+    AllReturned { values: &self.values }
+  }
+
+  /// Drops the values that have been in the pool for longer than the `MaxIdleTime`
+  /// and returns how many there were. Expired values are also evicted lazily
+  /// whenever a value is checked out or returned.
+  #[inline]
+  pub fn evict_idle(&self) -> usize {
+    self.values.evict_idle()
+  }
+
+  /// Removes a value from the pool and returns it wrapped in
+  /// a `Recycled smartpointer. If the pool is empty when the
+  /// method is called, a new value will be allocated.
+  #[inline] 
+  #[allow(clippy::new_ret_no_self)]
+  #[track_caller]
+  pub fn new(&self) -> Recycled<'_, T> {
+    let t = self.check_out();
+    Recycled::new(&self.values, t)
+  }
+
+  /// Removes a value from the pool, initializes it using the provided
+  /// source value, and returns it wrapped in a `Recycled` smartpointer.
+  /// If the pool is empty when the method is called, a new value will be
+  /// allocated.
+  #[inline(always)] 
+  #[track_caller]
+  pub fn new_from<A>(&self, source: A) -> Recycled<'_, T> where T: InitializeWith<A> {
+    let t = self.check_out();
+    Recycled::new_from(&self.values, t, source)
+  }
+
+  /// Removes a value from the pool and returns it wrapped in a `Recycled`
+  /// smartpointer. If the pool is empty, returns `None` instead of asking
+  /// the `Supply` for a new value.
+  #[inline]
+  #[track_caller]
+  pub fn try_new(&self) -> Option<Recycled<'_, T>> {
+    let t = self.check_out_idle()?;
+    Some(Recycled::new(&self.values, t))
+  }
+
+  /// Removes a value from the pool, initializes it using the provided
+  /// source value, and returns it wrapped in a `Recycled` smartpointer.
+  /// If the pool is empty, returns `None` instead of asking the `Supply`
+  /// for a new value.
+  #[inline]
+  #[track_caller]
+  pub fn try_new_from<A>(&self, source: A) -> Option<Recycled<'_, T>> where T: InitializeWith<A> {
+    let t = self.check_out_idle()?;
+    Some(Recycled::new_from(&self.values, t, source))
+  }
+
+  /// Removes a value from the pool and returns it wrapped in a `Recycled`
+  /// smartpointer. If the pool is empty, a new value is requested from the
+  /// pool's `TrySupply` and any error it returns is passed on as `PoolError::Supply`.
+  /// Under `AllocationPolicy::Forbid`, an empty pool returns `PoolError::Exhausted`.
+  ///
+  /// The infallible methods, like `Pool::new`, panic if the `TrySupply` fails.
+  #[inline]
+  #[track_caller]
+  pub fn try_get(&self) -> Result<Recycled<'_, T>, PoolError<E>> where E: 'static {
+    let t = self.try_check_out()?;
+    Ok(Recycled::new(&self.values, t))
+  }
+
+  /// Removes a value from the pool, initializes it using the provided
+  /// source value, and returns it wrapped in a `Recycled` smartpointer.
+  /// See `Pool::try_get`.
+  #[inline]
+  #[track_caller]
+  pub fn try_get_from<A>(&self, source: A) -> Result<Recycled<'_, T>, PoolError<E>> where
+      T: InitializeWith<A>,
+      E: 'static {
+    let t = self.try_check_out()?;
+    Ok(Recycled::new_from(&self.values, t, source))
+  }
+
+  /// Associates the provided value with the pool by wrapping it in a
+  /// `Recycled` smartpointer.
+  #[inline] 
+  #[track_caller]
+  pub fn attach(&self, value: T) -> Recycled<'_, T> {
+    let value = self.values.adopt(value);
+    Recycled::new(&self.values, value)
+  }
+
+  /// Removes a value from the pool and returns it without wrapping it in
+  /// a smartpointer. When the value goes out of scope it will not be
+  /// returned to the pool.
+  #[inline] 
+  pub fn detached(&self) -> T {
+    let value = self.values.remove_or_supply();
+    self.values.borrow_mut().stats.detached(false);
+    value
+  }
+
+  /// Removes a value from the pool to be wrapped in a smartpointer.
+  #[inline]
+  fn check_out(&self) -> T {
+    self.values.remove_or_create()
+  }
+
+  /// Removes a value from the pool to be wrapped in a smartpointer, creating one
+  /// with the `TrySupply` if the pool is empty.
+  #[inline]
+  fn try_check_out(&self) -> Result<T, PoolError<E>> where E: 'static {
+    if self.is_closed() {
+      return Err(PoolError::Closed);
+    }
+    self.values.evict_idle();
+    let value = if self.values.borrow().allocation_policy == AllocationPolicy::Forbid {
+      self.values.try_remove().ok_or(PoolError::Exhausted)?
+    } else {
+      self.values.remove_or_try_create().map_err(PoolError::from_supply)?
+    };
+    self.values.borrow_mut().stats.check_out();
+    Ok(value)
+  }
+
+  /// Removes a value from the pool to be wrapped in a smartpointer, if
+  /// there is one.
+  #[inline]
+  fn check_out_idle(&self) -> Option<T> {
+    if self.is_closed() {
+      return None;
+    }
+    self.values.evict_idle();
+    let value = self.values.try_remove();
+    if value.is_some() {
+      self.values.borrow_mut().stats.check_out();
+    }
+    value
+  }
+
+  /// Removes a value from the pool and returns it wrapped in
+  /// an `RcRecycled` smartpointer. If the pool is empty when the
+  /// method is called, a new value will be allocated.
+  #[inline] 
+  #[track_caller]
+  pub fn new_rc(&self) -> RcRecycled<T> {
+    let t = self.check_out();
+    let pool_reference = self.values.clone();
+    RcRecycled::new(pool_reference, t)
+  }
+ 
+  /// Removes a value from the pool, initializes it using the provided
+  /// source value, and returns it wrapped in an `RcRecycled` smartpointer.
+  /// If the pool is empty when the method is called, a new value will be
+  /// allocated.
+  #[inline(always)] 
+  #[track_caller]
+  pub fn new_rc_from<A>(&self, source: A) -> RcRecycled<T> where T: InitializeWith<A> {
+    let t = self.check_out();
+    let pool_reference = self.values.clone();
+    RcRecycled::new_from(pool_reference, t, source)
+  }
+
+  /// Associates the provided value with the pool by wrapping it in an
+  /// `RcRecycled` smartpointer.
+  #[inline] 
+  #[track_caller]
+  pub fn attach_rc(&self, value: T) -> RcRecycled<T> {
+    let value = self.values.adopt(value);
+    let pool_reference = self.values.clone();
+    RcRecycled::new(pool_reference, value)
+  }
+}
+
+/// A future that resolves once every smartpointer checked out of a `Pool` has
+/// been returned or detached. See `Pool::wait_until_all_returned`.
+pub struct AllReturned<'a, T: 'a> {
+  values: &'a PoolCell<T>
+}
+
+impl <'a, T> Future for AllReturned<'a, T> {
+  type Output = ();
+
+  fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+    if self.values.checked_out.get() == 0 {
+      return Poll::Ready(());
+    }
+    let mut waiting = self.values.waiting.borrow_mut();
+    if !waiting.iter().any(|waker| waker.will_wake(cx.waker())) {
+      waiting.push(cx.waker().clone());
+    }
+    Poll::Pending
+  }
+}
+
+impl <T, E> Drop for Pool <T, E> {
+  fn drop(&mut self) {
+    // Each `RcRecycled` that is still checked out keeps the storage alive.
+    let outstanding = Rc::strong_count(&self.values) - 1;
+    if outstanding == 0 {
+      return;
+    }
+    let observed = self.values.observer.try_borrow().is_ok_and(|observer| observer.is_some());
+    if observed {
+      let sites = self.outstanding_report(Duration::from_secs(0));
+      self.values.notify(|observer| observer.on_leak(outstanding, &sites));
+    }
+    let discard = (*self.values).borrow().return_to_dropped_pool == ReturnToDroppedPool::Discard;
+    if discard {
+      self.values.close();
+      self.values.release();
+    }
+  }
+}
+
+/// Produces a `PoolBuilder` instance
+/// 
+/// # Example
+/// 
+/// ```
+/// extern crate lifeguard;
+/// use lifeguard::*;
+///
+/// fn main() {
+///   let mut pool: Pool<String> = pool()
+///     .with(StartingSize(128))
+///     .with(MaxSize(4096))
+///     .with(Supplier(|| String::with_capacity(1024)))
+///     .build();
+/// }
+/// ```
+pub fn pool<T>() -> PoolBuilder<T> {
+  try_pool()
+}
+
+/// Produces a `PoolBuilder` for a pool whose values are created by a `TrySupplier`
+/// that can fail with an error of type `E`.
+///
+/// # Example
+///
+/// ```
+/// extern crate lifeguard;
+/// use lifeguard::*;
+///
+/// fn main() {
+///   let pool: Pool<String, &'static str> = try_pool()
+///     .with(StartingSize(0))
+///     .with(TrySupplier(|| Err("out of buffers")))
+///     .try_build()
+///     .unwrap();
+///   assert_eq!(PoolError::Supply("out of buffers"), pool.try_get().map(|value| value.detach()).unwrap_err());
+/// }
+/// ```
+pub fn try_pool<T, E>() -> PoolBuilder<T, E> {
+  PoolBuilder {
+    starting_size: 16,
+    max_size: usize::MAX,
+    supplier: Unset,
+    resetter: Unset,
+    collect_stats: true,
+    observer: None,
+    validator: None,
+    validate_on_checkout: false,
+    capacity_limit: None,
+    oversize_policy: OversizePolicy::Shrink,
+    byte_budget: None,
+    max_idle_time: None,
+    clock: None,
+    allocation_policy: AllocationPolicy::Allow,
+    reset_timing: ResetTiming::OnReturn,
+    track_checkouts: false,
+    return_to_dropped_pool: ReturnToDroppedPool::Keep,
+    error: PhantomData
+  }
+}
+
+/// Used to define settings for and ultimately create a `Pool`.
+///
+/// `S` and `R` record whether a `Supplier` or `TrySupplier` and a `Resetter` have
+/// been given. A pool of a type that doesn't implement `Recycleable` can only be
+/// built once both have.
+///
+/// # Example
+///
+/// ```
+/// extern crate lifeguard;
+/// use lifeguard::*;
+///
+/// struct Connection {
+///   buffer: Vec<u8>
+/// }
+///
+/// fn main() {
+///   let pool: Pool<Connection> = pool()
+///     .with(StartingSize(16))
+///     .with(Supplier(|| Connection { buffer: Vec::with_capacity(1024) }))
+///     .with(Resetter(|connection: &mut Connection| connection.buffer.clear()))
+///     .build();
+/// }
+/// ```
+///
+/// Without the `Resetter`, `Connection` would have to implement `Recycleable`:
+///
+/// ```compile_fail
+/// extern crate lifeguard;
+/// use lifeguard::*;
+///
+/// struct Connection {
+///   buffer: Vec<u8>
+/// }
+///
+/// fn main() {
+///   let pool: Pool<Connection> = pool()
+///     .with(Supplier(|| Connection { buffer: Vec::with_capacity(1024) }))
+///     .build();
+/// }
+/// ```
+pub struct PoolBuilder<T, E = Infallible, S = Unset, R = Unset> {
+  pub starting_size: usize,
+  pub max_size: usize,
+  pub supplier: S,
+  pub resetter: R,
+  pub collect_stats: bool,
+  pub observer: Option<Box<dyn PoolObserver<T>>>,
+  pub validator: Option<Validator<T>>,
+  pub validate_on_checkout: bool,
+  pub capacity_limit: Option<CapacityLimit<T>>,
+  pub oversize_policy: OversizePolicy,
+  pub byte_budget: Option<ByteBudget<T>>,
+  pub max_idle_time: Option<Duration>,
+  pub clock: Option<Box<dyn Clock>>,
+  pub allocation_policy: AllocationPolicy,
+  pub reset_timing: ResetTiming,
+  pub track_checkouts: bool,
+  pub return_to_dropped_pool: ReturnToDroppedPool,
+  error: PhantomData<fn() -> E>
+}
+
+impl <T, S, R> PoolBuilder<T, Infallible, S, R> where (S, R): Callbacks<T, Infallible> {
+  pub fn build(self) -> Pool<T> {
+    self.finish()
+  }
+}
+
+impl <T, E, S, R> PoolBuilder<T, E, S, R> where (S, R): Callbacks<T, E> {
+  /// Creates the `Pool`, returning the first error from its `TrySupply` if
+  /// one occurs while filling it up to its `StartingSize`.
+  pub fn try_build(self) -> Result<Pool<T, E>, PoolError<E>> where E: 'static {
+    let allocation_policy = self.allocation_policy;
+    let starting_size = self.starting_size;
+    let values = self.into_cell();
+    values.try_fill(starting_size).map_err(PoolError::from_supply)?;
+    values.borrow_mut().allocation_policy = allocation_policy;
+    Ok(Pool {
+      values: Rc::new(values),
+      error: PhantomData
+    })
+  }
+
+  /// Like `try_build`, but panics if a `TrySupply` fails while filling the pool
+  /// up to its `StartingSize`.
+  fn finish(self) -> Pool<T, E> {
+    let allocation_policy = self.allocation_policy;
+    let starting_size = self.starting_size;
+    let values = self.into_cell();
+    values.fill(starting_size);
+    // The pool is allowed to prewarm itself before the policy takes effect.
+    values.borrow_mut().allocation_policy = allocation_policy;
+    Pool {
+      values: Rc::new(values),
+      error: PhantomData
+    }
+  }
+
+  fn into_cell(self) -> PoolCell<T> {
+    let (source, fallback, resetter) = (self.supplier, self.resetter).into_parts();
+    let mut values: CappedCollection<T> = CappedCollection::new(self.max_size);
+    values.stats = Counters::new(self.collect_stats);
+    values.validate_on_checkout = self.validate_on_checkout;
+    values.capacity_limit = self.capacity_limit;
+    values.oversize_policy = self.oversize_policy;
+    values.byte_budget = self.byte_budget;
+    values.max_idle_time = self.max_idle_time;
+    values.reset_timing = self.reset_timing;
+    values.tracker = if self.track_checkouts { Some(CheckoutTracker::new()) } else { None };
+    values.return_to_dropped_pool = self.return_to_dropped_pool;
+    let mut cell = PoolCell::new(values, source, resetter);
+    cell.fallback = fallback;
+    if let Some(clock) = self.clock {
+      cell.clock = clock;
+    }
+    cell.observer = RefCell::new(self.observer);
+    cell.validator = RefCell::new(self.validator);
+    cell
+  }
+}
+
+impl <T, E, S, R> PoolBuilder<T, E, S, R> {
+  pub fn with<U>(self, setting: U) -> U::Output where
+      U: BuilderSetting<PoolBuilder<T, E, S, R>> {
+    setting.apply(self)
+  }
+
+  /// Replaces the supplier and the resetter, changing the builder's type to match.
+  fn map_callbacks<S2, R2, F>(self, map: F) -> PoolBuilder<T, E, S2, R2> where
+      F: FnOnce(S, R) -> (S2, R2) {
+    let (supplier, resetter) = map(self.supplier, self.resetter);
+    PoolBuilder {
+      starting_size: self.starting_size,
+      max_size: self.max_size,
+      supplier,
+      resetter,
+      collect_stats: self.collect_stats,
+      observer: self.observer,
+      validator: self.validator,
+      validate_on_checkout: self.validate_on_checkout,
+      capacity_limit: self.capacity_limit,
+      oversize_policy: self.oversize_policy,
+      byte_budget: self.byte_budget,
+      max_idle_time: self.max_idle_time,
+      clock: self.clock,
+      allocation_policy: self.allocation_policy,
+      reset_timing: self.reset_timing,
+      track_checkouts: self.track_checkouts,
+      return_to_dropped_pool: self.return_to_dropped_pool,
+      error: PhantomData
+    }
+  }
+}
+
+/// Produces a `PoolBuilder` for a pool of a type that doesn't implement
+/// `Recycleable`, using `supplier` to create its values and `resetter` to reset them.
+/// The same as `pool().with(Supplier(supplier)).with(Resetter(resetter))`.
+///
+/// # Example
+///
+/// ```
+/// extern crate lifeguard;
+/// use lifeguard::*;
+///
+/// struct Connection {
+///   buffer: Vec<u8>
+/// }
+///
+/// fn main() {
+///   let pool: Pool<Connection> = custom_pool(
+///       || Connection { buffer: Vec::with_capacity(1024) },
+///       |connection: &mut Connection| connection.buffer.clear())
+///     .with(StartingSize(16))
+///     .build();
+/// }
+/// ```
+pub fn custom_pool<T, S, F>(supplier: S, resetter: F) -> PoolBuilder<T, Infallible, Box<dyn Supply<Output=T>>, ResetFn<T>> where
+    S: Supply<Output=T> + 'static,
+    F: FnMut(&mut T) + 'static {
+  pool().with(Supplier(supplier)).with(Resetter(resetter))
+}
+
+/// Like `custom_pool`, but for values created by a `TrySupply` that can fail
+/// with an error of type `E`.
+pub fn try_custom_pool<T, E, S, F>(supplier: S, resetter: F) -> PoolBuilder<T, E, BoxedTrySupply<T, E>, ResetFn<T>> where
+    S: TrySupply<Output=T, Error=E> + 'static,
+    F: FnMut(&mut T) + 'static,
+    E: 'static {
+  try_pool().with(TrySupplier(supplier)).with(Resetter(resetter))
+}
+
+mod callbacks;
+pub use callbacks::Unset;
+use callbacks::{ErasedTrySupply, Source, Reset, Callbacks};
+mod std_types;
+mod memory;
+pub use memory::{Capacity, CapacityLimit, OversizePolicy, HeapSize, ByteBudget};
+mod stats;
+pub use stats::PoolStats;
+use stats::Counters;
+mod idle;
+pub use idle::{Clock, SystemClock};
+mod tracking;
+pub use tracking::OutstandingCheckouts;
+use tracking::CheckoutTracker;
+mod slots;
+mod sync;
+pub use sync::{sync_pool, SyncPool, SyncPoolBuilder, SyncRecycled, ArcRecycled};
+mod tiered;
+pub use tiered::{tiered_pool, TieredPool, TieredPoolBuilder, TieredRecycled};
+mod async_pool;
+pub use async_pool::{async_pool, AsyncPool, AsyncPoolBuilder, AsyncRecycled, Acquire, AcquireTimeout};
+
+pub mod settings {
+  use ::{PoolBuilder, BoxedTrySupply, PoolObserver, Validator, ResetFn, Capacity, CapacityLimit, OversizePolicy, HeapSize, ByteBudget,
+         Clock, AllocationPolicy, ResetTiming, ReturnToDroppedPool, SyncPoolBuilder, TieredPoolBuilder, AsyncPoolBuilder, Recycleable, Supply, TrySupply};
+  use std::time::Duration;
+    /// Implementing this trait allows a struct to act as a configuration
     /// parameter in the builder API.
   pub trait OptionSetter<T> {
-    fn set_option(self, T) -> T;
+    fn set_option(self, builder: T) -> T;
+  }
+
+    /// A setting that may change the type of the builder it's applied to, as the
+    /// `Supplier`, `TrySupplier` and `Resetter` settings do for a `PoolBuilder`.
+    /// Every `OptionSetter` is one.
+  pub trait BuilderSetting<T> {
+    type Output;
+    fn apply(self, builder: T) -> Self::Output;
+  }
+
+  impl <T, U> BuilderSetting<T> for U where U: OptionSetter<T> {
+    type Output = T;
+    #[inline]
+    fn apply(self, builder: T) -> T {
+      self.set_option(builder)
+    }
   }
   
     /// Specifies how many values should be requested from the Supplier at
@@ -616,33 +2036,305 @@ pub mod settings {
     /// Specifies a value implementing `Supply<Output=T>` that will be used to allocate
     /// new values. If unspecified, `T::new()` will be invoked.
   pub struct Supplier<S>(pub S) where S: Supply;
+    /// Specifies a value implementing `TrySupply<Output=T>` that will be used to allocate
+    /// new values whose creation can fail. Its errors are returned by `Pool::try_get` and
+    /// `PoolBuilder::try_build`. Replaces any `Supplier`. Start from `try_pool` to give
+    /// the pool the `TrySupply`'s error type.
+  pub struct TrySupplier<S>(pub S) where S: TrySupply;
+    /// Specifies a closure that returns a value to a like-new state before it is reused.
+    /// If unspecified, `T::reset()` will be invoked. A `PoolBuilder` given both a `Supplier`
+    /// and a `Resetter` can build a pool of a type that doesn't implement `Recycleable`.
+  pub struct Resetter<F>(pub F);
+    /// Specifies how many values a thread moves between its local cache and the
+    /// shared depot of a `TieredPool` at once. A thread's cache holds up to twice
+    /// this many values.
+  pub struct LocalBatchSize(pub usize);
+    /// Specifies the largest number of idle values the shared depot of a `TieredPool`
+    /// will hold before it will begin to drop batches being returned to it.
+  pub struct DepotCapacity(pub usize);
+    /// Specifies the largest number of values that can be checked out of the pool
+    /// at once. Further checkouts will wait until a value is returned.
+  pub struct MaxOutstanding(pub usize);
+    /// Specifies whether the `Pool` should keep the counters reported by `Pool::stats`.
+    /// Enabled by default; disabling it skips the bookkeeping on every checkout and return.
+  pub struct CollectStats(pub bool);
+    /// Specifies a value implementing `PoolObserver<T>` that will be notified as values
+    /// are created, checked out, returned and discarded.
+  pub struct Observer<O>(pub O);
+    /// Specifies a closure that decides whether a value being returned to the `Pool`
+    /// is fit to be reused. Values for which it returns `false` are dropped instead
+    /// of being reset and stored.
+  pub struct Validate<F>(pub F);
+    /// Specifies whether the `Validate` closure should also be applied to idle values as
+    /// they are checked out. Values that fail are dropped and the next idle value is tried,
+    /// falling back to the `Supplier` if none pass. Disabled by default.
+  pub struct ValidateOnCheckout(pub bool);
+    /// Specifies the largest capacity a value returned to the `Pool` may have. Values
+    /// with larger buffers are shrunk or dropped depending on the `OversizePolicy`.
+  pub struct MaxRetainedCapacity(pub usize);
+    /// Specifies the largest number of heap bytes, as measured by `HeapSize`, that
+    /// the values remaining in the `Pool` may hold. Returns that would exceed it are discarded.
+  pub struct MaxRetainedBytes(pub usize);
+    /// Specifies how long a value may sit unused in the `Pool` before it is evicted.
+  pub struct MaxIdleTime(pub Duration);
+    /// Specifies the `Clock` used to measure how long values have been idle or checked out.
+  pub struct TimeSource<C>(pub C);
+    /// Specifies whether the `Pool` should record where and when each smartpointer was
+    /// checked out, for `Pool::outstanding_report` and `PoolObserver::on_leak`.
+    /// Disabled by default, as it costs a map insertion and a clock read per checkout.
+  pub struct TrackCheckouts(pub bool);
   
-  impl <T> OptionSetter<PoolBuilder<T>> for StartingSize where T: Recycleable {
-    fn set_option(self, mut builder: PoolBuilder<T>) -> PoolBuilder<T> {
+  impl <T, E, S, R> OptionSetter<PoolBuilder<T, E, S, R>> for StartingSize {
+    fn set_option(self, mut builder: PoolBuilder<T, E, S, R>) -> PoolBuilder<T, E, S, R> {
       let StartingSize(size) = self;
       builder.starting_size = size;
       builder
     }
   }
    
-  impl <T> OptionSetter<PoolBuilder<T>> for MaxSize where T: Recycleable {
-    fn set_option(self, mut builder: PoolBuilder<T>) -> PoolBuilder<T> {
+  impl <T, E, S, R> OptionSetter<PoolBuilder<T, E, S, R>> for MaxSize {
+    fn set_option(self, mut builder: PoolBuilder<T, E, S, R>) -> PoolBuilder<T, E, S, R> {
       let MaxSize(size) = self;
       builder.max_size = size;
       builder
     }
   }
   
-  impl <T, S> OptionSetter<PoolBuilder<T>> for Supplier<S> where
-      S: Supply<Output=T> + 'static,
+  impl <T, E, S, R, U> BuilderSetting<PoolBuilder<T, E, S, R>> for Supplier<U> where
+      U: Supply<Output=T> + 'static {
+    type Output = PoolBuilder<T, E, Box<dyn Supply<Output=T>>, R>;
+    fn apply(self, builder: PoolBuilder<T, E, S, R>) -> Self::Output {
+      let Supplier(supplier) = self;
+      builder.map_callbacks(|_, resetter| (Box::new(supplier) as Box<dyn Supply<Output=T>>, resetter))
+    }
+  }
+
+  impl <T, E, S, R, U> BuilderSetting<PoolBuilder<T, E, S, R>> for TrySupplier<U> where
+      U: TrySupply<Output=T, Error=E> + 'static,
+      E: 'static {
+    type Output = PoolBuilder<T, E, BoxedTrySupply<T, E>, R>;
+    fn apply(self, builder: PoolBuilder<T, E, S, R>) -> Self::Output {
+      let TrySupplier(supplier) = self;
+      builder.map_callbacks(|_, resetter| (BoxedTrySupply::new(supplier), resetter))
+    }
+  }
+
+  impl <T, E, S, R, F> BuilderSetting<PoolBuilder<T, E, S, R>> for Resetter<F> where
+      F: FnMut(&mut T) + 'static {
+    type Output = PoolBuilder<T, E, S, ResetFn<T>>;
+    fn apply(self, builder: PoolBuilder<T, E, S, R>) -> Self::Output {
+      let Resetter(resetter) = self;
+      builder.map_callbacks(|supplier, _| (supplier, Box::new(resetter) as ResetFn<T>))
+    }
+  }
+
+  impl <T, E, S, R> OptionSetter<PoolBuilder<T, E, S, R>> for CollectStats {
+    fn set_option(self, mut builder: PoolBuilder<T, E, S, R>) -> PoolBuilder<T, E, S, R> {
+      let CollectStats(enabled) = self;
+      builder.collect_stats = enabled;
+      builder
+    }
+  }
+
+  impl <T, E, S, R, F> OptionSetter<PoolBuilder<T, E, S, R>> for Validate<F> where
+      F: FnMut(&T) -> bool + 'static {
+    fn set_option(self, mut builder: PoolBuilder<T, E, S, R>) -> PoolBuilder<T, E, S, R> {
+      let Validate(validator) = self;
+      builder.validator = Some(Box::new(validator) as Validator<T>);
+      builder
+    }
+  }
+
+  impl <T, E, S, R> OptionSetter<PoolBuilder<T, E, S, R>> for ValidateOnCheckout {
+    fn set_option(self, mut builder: PoolBuilder<T, E, S, R>) -> PoolBuilder<T, E, S, R> {
+      let ValidateOnCheckout(enabled) = self;
+      builder.validate_on_checkout = enabled;
+      builder
+    }
+  }
+
+  impl <T, E, S, R> OptionSetter<PoolBuilder<T, E, S, R>> for MaxRetainedCapacity where T: Capacity {
+    fn set_option(self, mut builder: PoolBuilder<T, E, S, R>) -> PoolBuilder<T, E, S, R> {
+      let MaxRetainedCapacity(max) = self;
+      builder.capacity_limit = Some(CapacityLimit::new(max));
+      builder
+    }
+  }
+
+  impl <T, E, S, R> OptionSetter<PoolBuilder<T, E, S, R>> for MaxRetainedBytes where T: HeapSize {
+    fn set_option(self, mut builder: PoolBuilder<T, E, S, R>) -> PoolBuilder<T, E, S, R> {
+      let MaxRetainedBytes(max) = self;
+      builder.byte_budget = Some(ByteBudget::new(max));
+      builder
+    }
+  }
+
+  impl <T, E, S, R> OptionSetter<PoolBuilder<T, E, S, R>> for MaxIdleTime {
+    fn set_option(self, mut builder: PoolBuilder<T, E, S, R>) -> PoolBuilder<T, E, S, R> {
+      let MaxIdleTime(max_idle_time) = self;
+      builder.max_idle_time = Some(max_idle_time);
+      builder
+    }
+  }
+
+  impl <T, E, S, R, C> OptionSetter<PoolBuilder<T, E, S, R>> for TimeSource<C> where
+    C: Clock + 'static {
+    fn set_option(self, mut builder: PoolBuilder<T, E, S, R>) -> PoolBuilder<T, E, S, R> {
+      let TimeSource(clock) = self;
+      builder.clock = Some(Box::new(clock));
+      builder
+    }
+  }
+
+  impl <T, E, S, R> OptionSetter<PoolBuilder<T, E, S, R>> for AllocationPolicy {
+    fn set_option(self, mut builder: PoolBuilder<T, E, S, R>) -> PoolBuilder<T, E, S, R> {
+      builder.allocation_policy = self;
+      builder
+    }
+  }
+
+  impl <T, E, S, R> OptionSetter<PoolBuilder<T, E, S, R>> for TrackCheckouts {
+    fn set_option(self, mut builder: PoolBuilder<T, E, S, R>) -> PoolBuilder<T, E, S, R> {
+      let TrackCheckouts(enabled) = self;
+      builder.track_checkouts = enabled;
+      builder
+    }
+  }
+
+  impl <T, E, S, R> OptionSetter<PoolBuilder<T, E, S, R>> for ReturnToDroppedPool {
+    fn set_option(self, mut builder: PoolBuilder<T, E, S, R>) -> PoolBuilder<T, E, S, R> {
+      builder.return_to_dropped_pool = self;
+      builder
+    }
+  }
+
+  impl <T, E, S, R> OptionSetter<PoolBuilder<T, E, S, R>> for ResetTiming {
+    fn set_option(self, mut builder: PoolBuilder<T, E, S, R>) -> PoolBuilder<T, E, S, R> {
+      builder.reset_timing = self;
+      builder
+    }
+  }
+
+  impl <T, E, S, R> OptionSetter<PoolBuilder<T, E, S, R>> for OversizePolicy {
+    fn set_option(self, mut builder: PoolBuilder<T, E, S, R>) -> PoolBuilder<T, E, S, R> {
+      builder.oversize_policy = self;
+      builder
+    }
+  }
+
+  impl <T, E, S, R, O> OptionSetter<PoolBuilder<T, E, S, R>> for Observer<O> where
+      O: PoolObserver<T> + 'static {
+    fn set_option(self, mut builder: PoolBuilder<T, E, S, R>) -> PoolBuilder<T, E, S, R> {
+      let Observer(observer) = self;
+      builder.observer = Some(Box::new(observer) as Box<dyn PoolObserver<T>>);
+      builder
+    }
+  }
+
+  impl <T> OptionSetter<SyncPoolBuilder<T>> for StartingSize where T: Recycleable {
+    fn set_option(self, mut builder: SyncPoolBuilder<T>) -> SyncPoolBuilder<T> {
+      let StartingSize(size) = self;
+      builder.starting_size = size;
+      builder
+    }
+  }
+
+  impl <T> OptionSetter<SyncPoolBuilder<T>> for MaxSize where T: Recycleable {
+    fn set_option(self, mut builder: SyncPoolBuilder<T>) -> SyncPoolBuilder<T> {
+      let MaxSize(size) = self;
+      builder.max_size = size;
+      builder
+    }
+  }
+
+  impl <T, S> OptionSetter<SyncPoolBuilder<T>> for Supplier<S> where
+      S: Supply<Output=T> + Send + 'static,
       T: Recycleable {
-    fn set_option(self, mut builder: PoolBuilder<T>) -> PoolBuilder<T> {
+    fn set_option(self, mut builder: SyncPoolBuilder<T>) -> SyncPoolBuilder<T> {
+      let Supplier(supplier) = self;
+      builder.supplier = Some(Box::new(supplier) as Box<dyn Supply<Output=T> + Send>);
+      builder
+    }
+  }
+
+  impl <T> OptionSetter<SyncPoolBuilder<T>> for MaxOutstanding where T: Recycleable {
+    fn set_option(self, mut builder: SyncPoolBuilder<T>) -> SyncPoolBuilder<T> {
+      let MaxOutstanding(limit) = self;
+      builder.max_outstanding = limit;
+      builder
+    }
+  }
+
+  impl <T> OptionSetter<TieredPoolBuilder<T>> for StartingSize where T: Recycleable + Send + 'static {
+    fn set_option(self, mut builder: TieredPoolBuilder<T>) -> TieredPoolBuilder<T> {
+      let StartingSize(size) = self;
+      builder.starting_size = size;
+      builder
+    }
+  }
+
+  impl <T> OptionSetter<TieredPoolBuilder<T>> for LocalBatchSize where T: Recycleable + Send + 'static {
+    fn set_option(self, mut builder: TieredPoolBuilder<T>) -> TieredPoolBuilder<T> {
+      let LocalBatchSize(size) = self;
+      builder.batch_size = size;
+      builder
+    }
+  }
+
+  impl <T> OptionSetter<TieredPoolBuilder<T>> for DepotCapacity where T: Recycleable + Send + 'static {
+    fn set_option(self, mut builder: TieredPoolBuilder<T>) -> TieredPoolBuilder<T> {
+      let DepotCapacity(size) = self;
+      builder.depot_capacity = size;
+      builder
+    }
+  }
+
+  impl <T, S> OptionSetter<TieredPoolBuilder<T>> for Supplier<S> where
+      S: Supply<Output=T> + Send + 'static,
+      T: Recycleable + Send + 'static {
+    fn set_option(self, mut builder: TieredPoolBuilder<T>) -> TieredPoolBuilder<T> {
+      let Supplier(supplier) = self;
+      builder.supplier = Some(Box::new(supplier) as Box<dyn Supply<Output=T> + Send>);
+      builder
+    }
+  }
+
+  impl <T> OptionSetter<AsyncPoolBuilder<T>> for StartingSize where T: Recycleable + Send {
+    fn set_option(self, mut builder: AsyncPoolBuilder<T>) -> AsyncPoolBuilder<T> {
+      let StartingSize(size) = self;
+      builder.starting_size = size;
+      builder
+    }
+  }
+
+  impl <T> OptionSetter<AsyncPoolBuilder<T>> for MaxSize where T: Recycleable + Send {
+    fn set_option(self, mut builder: AsyncPoolBuilder<T>) -> AsyncPoolBuilder<T> {
+      let MaxSize(size) = self;
+      builder.max_size = size;
+      builder
+    }
+  }
+
+  impl <T> OptionSetter<AsyncPoolBuilder<T>> for MaxOutstanding where T: Recycleable + Send {
+    fn set_option(self, mut builder: AsyncPoolBuilder<T>) -> AsyncPoolBuilder<T> {
+      let MaxOutstanding(limit) = self;
+      builder.max_outstanding = limit;
+      builder
+    }
+  }
+
+  impl <T, S> OptionSetter<AsyncPoolBuilder<T>> for Supplier<S> where
+      S: Supply<Output=T> + Send + 'static,
+      T: Recycleable + Send {
+    fn set_option(self, mut builder: AsyncPoolBuilder<T>) -> AsyncPoolBuilder<T> {
       let Supplier(supplier) = self;
-      builder.supplier = Some(Box::new(supplier) as Box<dyn Supply<Output=T>>);
+      builder.supplier = Some(Box::new(supplier) as Box<dyn Supply<Output=T> + Send>);
       builder
     }
   }
 }
 
-pub use settings::{OptionSetter, StartingSize, MaxSize, Supplier};
+pub use settings::{OptionSetter, BuilderSetting, StartingSize, MaxSize, Supplier, TrySupplier, Resetter, LocalBatchSize, DepotCapacity,
+                   MaxOutstanding, CollectStats, Observer, Validate, ValidateOnCheckout,
+                   MaxRetainedCapacity, MaxRetainedBytes, MaxIdleTime, TimeSource, TrackCheckouts};
 use std::mem;
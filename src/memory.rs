@@ -0,0 +1,357 @@
+use std::collections::{HashMap, HashSet, BTreeMap, BTreeSet, BinaryHeap, LinkedList, VecDeque};
+use std::ffi::OsString;
+use std::hash::{BuildHasher, Hash};
+use std::io::Cursor;
+use std::mem;
+use std::path::PathBuf;
+
+/// Implemented by types that allocate room for their contents ahead of time.
+/// Pools use it to keep values with oversized buffers from staying pinned in
+/// memory after a single large use. See `MaxRetainedCapacity`.
+pub trait Capacity {
+  /// Returns the number of elements the value can hold without reallocating.
+  fn capacity(&self) -> usize;
+  /// Shrinks the value's capacity to at least `min_capacity`, releasing the rest.
+  fn shrink_to(&mut self, min_capacity: usize);
+}
+
+impl Capacity for String {
+  #[inline]
+  fn capacity(&self) -> usize {
+    String::capacity(self)
+  }
+  #[inline]
+  fn shrink_to(&mut self, min_capacity: usize) {
+    String::shrink_to(self, min_capacity)
+  }
+}
+
+impl <T> Capacity for Vec<T> {
+  #[inline]
+  fn capacity(&self) -> usize {
+    Vec::capacity(self)
+  }
+  #[inline]
+  fn shrink_to(&mut self, min_capacity: usize) {
+    Vec::shrink_to(self, min_capacity)
+  }
+}
+
+impl <T> Capacity for VecDeque<T> {
+  #[inline]
+  fn capacity(&self) -> usize {
+    VecDeque::capacity(self)
+  }
+  #[inline]
+  fn shrink_to(&mut self, min_capacity: usize) {
+    VecDeque::shrink_to(self, min_capacity)
+  }
+}
+
+impl <K, V, S> Capacity for HashMap<K, V, S> where K: Eq + Hash, S: BuildHasher {
+  #[inline]
+  fn capacity(&self) -> usize {
+    HashMap::capacity(self)
+  }
+  #[inline]
+  fn shrink_to(&mut self, min_capacity: usize) {
+    HashMap::shrink_to(self, min_capacity)
+  }
+}
+
+impl <T, S> Capacity for HashSet<T, S> where T: Eq + Hash, S: BuildHasher {
+  #[inline]
+  fn capacity(&self) -> usize {
+    HashSet::capacity(self)
+  }
+  #[inline]
+  fn shrink_to(&mut self, min_capacity: usize) {
+    HashSet::shrink_to(self, min_capacity)
+  }
+}
+
+impl <T> Capacity for BinaryHeap<T> {
+  #[inline]
+  fn capacity(&self) -> usize {
+    BinaryHeap::capacity(self)
+  }
+  #[inline]
+  fn shrink_to(&mut self, min_capacity: usize) {
+    BinaryHeap::shrink_to(self, min_capacity)
+  }
+}
+
+impl Capacity for PathBuf {
+  #[inline]
+  fn capacity(&self) -> usize {
+    PathBuf::capacity(self)
+  }
+  #[inline]
+  fn shrink_to(&mut self, min_capacity: usize) {
+    PathBuf::shrink_to(self, min_capacity)
+  }
+}
+
+impl Capacity for OsString {
+  #[inline]
+  fn capacity(&self) -> usize {
+    OsString::capacity(self)
+  }
+  #[inline]
+  fn shrink_to(&mut self, min_capacity: usize) {
+    OsString::shrink_to(self, min_capacity)
+  }
+}
+
+impl Capacity for Cursor<Vec<u8>> {
+  #[inline]
+  fn capacity(&self) -> usize {
+    self.get_ref().capacity()
+  }
+  #[inline]
+  fn shrink_to(&mut self, min_capacity: usize) {
+    self.get_mut().shrink_to(min_capacity)
+  }
+}
+
+impl <T> Capacity for Box<T> where T: Capacity {
+  #[inline]
+  fn capacity(&self) -> usize {
+    (**self).capacity()
+  }
+  #[inline]
+  fn shrink_to(&mut self, min_capacity: usize) {
+    (**self).shrink_to(min_capacity)
+  }
+}
+
+/// `None` has no capacity.
+impl <T> Capacity for Option<T> where T: Capacity {
+  #[inline]
+  fn capacity(&self) -> usize {
+    self.as_ref().map_or(0, T::capacity)
+  }
+  #[inline]
+  fn shrink_to(&mut self, min_capacity: usize) {
+    if let Some(ref mut value) = *self {
+      value.shrink_to(min_capacity)
+    }
+  }
+}
+
+/// Determines what a `Pool` does with a returned value whose capacity is
+/// larger than its `MaxRetainedCapacity`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OversizePolicy {
+  /// Reset the value and shrink it down to the limit before storing it.
+  Shrink,
+  /// Drop the value instead of storing it.
+  Discard
+}
+
+/// The limit set by `MaxRetainedCapacity`, along with the `Capacity` methods
+/// needed to enforce it.
+pub struct CapacityLimit<T> {
+  max: usize,
+  capacity: fn(&T) -> usize,
+  shrink_to: fn(&mut T, usize)
+}
+
+impl <T> CapacityLimit<T> where T: Capacity {
+  #[inline]
+  pub fn new(max: usize) -> CapacityLimit<T> {
+    CapacityLimit { max, capacity: T::capacity, shrink_to: T::shrink_to }
+  }
+}
+
+impl <T> CapacityLimit<T> {
+  /// Returns the largest capacity a value may have and still be retained as-is.
+  #[inline]
+  pub fn max(&self) -> usize {
+    self.max
+  }
+
+  #[inline]
+  pub(crate) fn is_exceeded_by(&self, value: &T) -> bool {
+    (self.capacity)(value) > self.max
+  }
+
+  #[inline]
+  pub(crate) fn shrink(&self, value: &mut T) {
+    (self.shrink_to)(value, self.max)
+  }
+}
+
+/// Implemented by types that own memory on the heap. Pools use it to keep the
+/// total memory held by idle values within a budget. See `MaxRetainedBytes`.
+pub trait HeapSize {
+  /// Returns the number of heap bytes owned by the value. The built-in impls
+  /// count allocated capacity, not length, and do not follow pointers held by
+  /// the elements of a collection. The sizes of node-based collections like
+  /// `BTreeMap` and `LinkedList` are estimates.
+  fn heap_size(&self) -> usize;
+}
+
+impl HeapSize for String {
+  #[inline]
+  fn heap_size(&self) -> usize {
+    self.capacity()
+  }
+}
+
+impl <T> HeapSize for Vec<T> {
+  #[inline]
+  fn heap_size(&self) -> usize {
+    self.capacity() * mem::size_of::<T>()
+  }
+}
+
+impl <T> HeapSize for VecDeque<T> {
+  #[inline]
+  fn heap_size(&self) -> usize {
+    self.capacity() * mem::size_of::<T>()
+  }
+}
+
+// Hash tables store one control byte alongside each bucket.
+impl <K, V, S> HeapSize for HashMap<K, V, S> {
+  #[inline]
+  fn heap_size(&self) -> usize {
+    self.capacity() * (mem::size_of::<(K, V)>() + 1)
+  }
+}
+
+impl <T, S> HeapSize for HashSet<T, S> {
+  #[inline]
+  fn heap_size(&self) -> usize {
+    self.capacity() * (mem::size_of::<T>() + 1)
+  }
+}
+
+impl <T> HeapSize for BinaryHeap<T> {
+  #[inline]
+  fn heap_size(&self) -> usize {
+    self.capacity() * mem::size_of::<T>()
+  }
+}
+
+// B-tree nodes are allocated as they fill up, so their entries are a fair estimate.
+impl <K, V> HeapSize for BTreeMap<K, V> {
+  #[inline]
+  fn heap_size(&self) -> usize {
+    self.len() * mem::size_of::<(K, V)>()
+  }
+}
+
+impl <T> HeapSize for BTreeSet<T> {
+  #[inline]
+  fn heap_size(&self) -> usize {
+    self.len() * mem::size_of::<T>()
+  }
+}
+
+// Each element lives in its own node alongside two links.
+impl <T> HeapSize for LinkedList<T> {
+  #[inline]
+  fn heap_size(&self) -> usize {
+    self.len() * (mem::size_of::<T>() + 2 * mem::size_of::<usize>())
+  }
+}
+
+impl HeapSize for PathBuf {
+  #[inline]
+  fn heap_size(&self) -> usize {
+    self.capacity()
+  }
+}
+
+impl HeapSize for OsString {
+  #[inline]
+  fn heap_size(&self) -> usize {
+    self.capacity()
+  }
+}
+
+impl HeapSize for Cursor<Vec<u8>> {
+  #[inline]
+  fn heap_size(&self) -> usize {
+    self.get_ref().capacity()
+  }
+}
+
+/// Counts the box's own allocation as well as the heap bytes of its contents.
+impl <T> HeapSize for Box<T> where T: HeapSize {
+  #[inline]
+  fn heap_size(&self) -> usize {
+    mem::size_of::<T>() + (**self).heap_size()
+  }
+}
+
+impl <T> HeapSize for Option<T> where T: HeapSize {
+  #[inline]
+  fn heap_size(&self) -> usize {
+    self.as_ref().map_or(0, T::heap_size)
+  }
+}
+
+impl <T, const N: usize> HeapSize for [T; N] where T: HeapSize {
+  #[inline]
+  fn heap_size(&self) -> usize {
+    self.iter().map(T::heap_size).sum()
+  }
+}
+
+macro_rules! impl_tuple_heap_size {
+  ($(($($element:ident $index:tt),+))+) => {
+    $(
+      impl <$($element),+> HeapSize for ($($element,)+) where $($element: HeapSize),+ {
+        #[inline]
+        fn heap_size(&self) -> usize {
+          0 $(+ self.$index.heap_size())+
+        }
+      }
+    )+
+  }
+}
+
+impl_tuple_heap_size! {
+  (A 0)
+  (A 0, B 1)
+  (A 0, B 1, C 2)
+  (A 0, B 1, C 2, D 3)
+  (A 0, B 1, C 2, D 3, E 4)
+  (A 0, B 1, C 2, D 3, E 4, F 5)
+  (A 0, B 1, C 2, D 3, E 4, F 5, G 6)
+  (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7)
+  (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8)
+  (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9)
+  (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10)
+  (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11)
+}
+
+/// The budget set by `MaxRetainedBytes`, along with the `HeapSize` method
+/// needed to enforce it.
+pub struct ByteBudget<T> {
+  max: usize,
+  heap_size: fn(&T) -> usize
+}
+
+impl <T> ByteBudget<T> where T: HeapSize {
+  #[inline]
+  pub fn new(max: usize) -> ByteBudget<T> {
+    ByteBudget { max, heap_size: T::heap_size }
+  }
+}
+
+impl <T> ByteBudget<T> {
+  /// Returns the largest number of heap bytes the pool's idle values may hold.
+  #[inline]
+  pub fn max(&self) -> usize {
+    self.max
+  }
+
+  #[inline]
+  pub(crate) fn size_of(&self, value: &T) -> usize {
+    (self.heap_size)(value)
+  }
+}
@@ -0,0 +1,305 @@
+use std::collections::VecDeque;
+use std::mem;
+use std::sync::{Mutex, MutexGuard};
+use std::task::Waker;
+use std::thread::{self, Thread, ThreadId};
+
+use ::{try_reset, Recycleable, Recycler, Supply};
+
+/// Something waiting in line for a slot: a thread parked in `SyncPool::get_timeout`
+/// or a task pending in `AsyncPool::acquire`.
+pub trait Notify {
+  /// Tells the waiter that a slot has been handed to it.
+  fn notify(self);
+}
+
+impl Notify for Thread {
+  #[inline]
+  fn notify(self) {
+    self.unpark()
+  }
+}
+
+impl Notify for Waker {
+  #[inline]
+  fn notify(self) {
+    self.wake()
+  }
+}
+
+/// A caller waiting in line for a slot.
+struct Waiter<W> {
+  id: usize,
+  notify: Option<W>,
+  // Set once a slot has been handed to this waiter; it may now claim a value.
+  granted: bool
+}
+
+/// The thread-safe counterpart of `CappedCollection`, which also limits how many
+/// values can be checked out at once.
+struct SlotState<T, W> {
+  values: Vec<T>,
+  cap: usize,
+  outstanding: usize,
+  max_outstanding: usize,
+  waiters: VecDeque<Waiter<W>>,
+  next_waiter_id: usize,
+  // The thread that's running the supplier, if any.
+  supplying: Option<ThreadId>
+}
+
+impl <T, W> SlotState<T, W> {
+  #[inline]
+  fn is_full(&self) -> bool {
+    self.values.len() >= self.cap
+  }
+
+  /// Gives up a slot. If a caller is waiting, the slot is handed to it directly so
+  /// that newer callers can't jump the queue; it's returned so that it can be
+  /// notified once the lock has been released.
+  #[inline]
+  fn release(&mut self) -> Option<W> {
+    match self.waiters.iter_mut().find(|waiter| !waiter.granted) {
+      Some(waiter) => {
+        waiter.granted = true;
+        waiter.notify.take()
+      },
+      None => {
+        self.outstanding -= 1;
+        None
+      }
+    }
+  }
+}
+
+/// The storage shared by a `SyncPool` or an `AsyncPool` and its smartpointers. A slot
+/// is taken for every value checked out, up to `MaxOutstanding`, and callers that find
+/// none free wait in line for one, notified through `W`. The supplier has a lock of its
+/// own so that the values aren't locked while it runs.
+pub struct Slots<T, W> where T: Recycleable {
+  state: Mutex<SlotState<T, W>>,
+  supplier: Mutex<Box<dyn Supply<Output=T> + Send>>
+}
+
+/// Locks `mutex`. A panic in user code (a `Supply` or `reset` implementation)
+/// cannot leave the values or the supplier in an inconsistent state, so
+/// poisoning is ignored.
+#[inline]
+fn lock<U: ?Sized>(mutex: &Mutex<U>) -> MutexGuard<'_, U> {
+  mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Gives up a slot that was taken for a value that never arrived, if the
+/// supplier panics before it's forgotten.
+struct Reservation<'a, T: 'a, W: 'a>(&'a Slots<T, W>) where T: Recycleable, W: Notify;
+
+impl <'a, T, W> Drop for Reservation<'a, T, W> where T: Recycleable, W: Notify {
+  fn drop(&mut self) {
+    self.0.release();
+  }
+}
+
+/// Clears `SlotState::supplying` once the supplier returns or panics.
+struct Supplying<'a, T: 'a, W: 'a>(&'a Slots<T, W>) where T: Recycleable;
+
+impl <'a, T, W> Drop for Supplying<'a, T, W> where T: Recycleable {
+  fn drop(&mut self) {
+    lock(&self.0.state).supplying = None;
+  }
+}
+
+impl <T, W> Slots<T, W> where T: Recycleable, W: Notify {
+  pub fn new(mut supplier: Box<dyn Supply<Output=T> + Send>, starting_size: usize, max_size: usize,
+             max_outstanding: usize) -> Slots<T, W> {
+    use std::cmp;
+    let starting_size = cmp::min(starting_size, max_size);
+    let values: Vec<T> =
+      (0..starting_size)
+      .map(|_| supplier.get() )
+      .collect();
+    let state = SlotState {
+      values,
+      cap: max_size,
+      outstanding: 0,
+      max_outstanding,
+      waiters: VecDeque::new(),
+      next_waiter_id: 0,
+      supplying: None
+    };
+    Slots {
+      state: Mutex::new(state),
+      supplier: Mutex::new(supplier)
+    }
+  }
+
+  /// Returns the number of values remaining in the pool.
+  #[inline]
+  pub fn len(&self) -> usize {
+    lock(&self.state).values.len()
+  }
+
+  /// Returns the maximum number of values the pool can hold.
+  #[inline]
+  pub fn cap(&self) -> usize {
+    lock(&self.state).cap
+  }
+
+  /// Returns the number of slots taken.
+  #[inline]
+  pub fn outstanding(&self) -> usize {
+    lock(&self.state).outstanding
+  }
+
+  #[inline]
+  pub fn max_outstanding(&self) -> usize {
+    lock(&self.state).max_outstanding
+  }
+
+  /// Returns the number of callers waiting in line.
+  #[inline]
+  pub fn waiting(&self) -> usize {
+    lock(&self.state).waiters.len()
+  }
+
+  /// Takes a slot if one is free and nobody is waiting for it.
+  #[inline]
+  pub fn try_take_slot(&self) -> bool {
+    let mut state = lock(&self.state);
+    if state.waiters.is_empty() && state.outstanding < state.max_outstanding {
+      state.outstanding += 1;
+      true
+    } else {
+      false
+    }
+  }
+
+  /// Like `try_take_slot`, but if no slot is free, joins the back of the line instead
+  /// and returns the caller's id. `notify` is used once a slot is handed to it.
+  pub fn take_slot_or_join(&self, notify: W) -> Option<usize> {
+    let mut state = lock(&self.state);
+    if state.waiters.is_empty() && state.outstanding < state.max_outstanding {
+      state.outstanding += 1;
+      return None;
+    }
+    let id = state.next_waiter_id;
+    state.next_waiter_id = state.next_waiter_id.wrapping_add(1);
+    state.waiters.push_back(Waiter { id, notify: Some(notify), granted: false });
+    Some(id)
+  }
+
+  /// Takes a slot for a value that is checked out regardless of `MaxOutstanding`.
+  #[inline]
+  pub fn take_extra_slot(&self) {
+    lock(&self.state).outstanding += 1;
+  }
+
+  /// Leaves the line and returns true if the waiter `id` has been handed a slot.
+  /// Otherwise it keeps waiting, to be notified through `notify` if one is given.
+  pub fn take_granted(&self, id: usize, notify: Option<W>) -> bool {
+    let mut state = lock(&self.state);
+    let index = state.waiters.iter().position(|waiter| waiter.id == id)
+      .expect("Waiter was removed from the queue.");
+    // A granted slot has already been counted as outstanding.
+    if state.waiters[index].granted {
+      state.waiters.remove(index);
+      return true;
+    }
+    if notify.is_some() {
+      state.waiters[index].notify = notify;
+    }
+    false
+  }
+
+  /// Leaves the line without a value. If the waiter `id` had already been handed
+  /// a slot, it's passed along to the next caller in line.
+  pub fn leave(&self, id: usize) {
+    let next = {
+      let mut state = lock(&self.state);
+      let index = match state.waiters.iter().position(|waiter| waiter.id == id) {
+        Some(index) => index,
+        None => return
+      };
+      let waiter = state.waiters.remove(index).unwrap();
+      if waiter.granted { state.release() } else { None }
+    };
+    if let Some(next) = next {
+      next.notify();
+    }
+  }
+
+  /// Gives up a slot, handing it to the longest-waiting caller, if any.
+  #[inline]
+  pub fn release(&self) {
+    let next = lock(&self.state).release();
+    if let Some(next) = next {
+      next.notify();
+    }
+  }
+
+  /// Takes a stored value, or asks the supplier for a new one once the values
+  /// have been unlocked. A supplier that asks its own pool for a value gets one
+  /// from `T::new` instead of waiting on itself.
+  pub fn remove_or_supply(&self) -> T {
+    let current = {
+      let mut state = lock(&self.state);
+      if let Some(value) = state.values.pop() {
+        return value;
+      }
+      let current = thread::current().id();
+      if state.supplying == Some(current) {
+        return T::new();
+      }
+      current
+    };
+    let mut supplier = lock(&self.supplier);
+    lock(&self.state).supplying = Some(current);
+    let _supplying = Supplying(self);
+    supplier.get()
+  }
+
+  /// Fills a slot that has already been taken, giving it up again if the
+  /// supplier panics.
+  pub fn fill_slot(&self) -> T {
+    let reservation = Reservation(self);
+    let value = self.remove_or_supply();
+    mem::forget(reservation);
+    value
+  }
+}
+
+impl <T, W> Recycler<T> for Slots<T, W> where T: Recycleable, W: Notify {
+  #[inline]
+  fn recycle(&self, value: T) {
+    // Only reset the value if there's room to keep it; it's reset outside of the lock.
+    let is_full = lock(&self.state).is_full();
+    let reset = if is_full { Err(value) } else { try_reset(value, T::reset) };
+    let (next, discarded) = {
+      let mut state = lock(&self.state);
+      // Another caller may have filled the pool while we were resetting.
+      let discarded = match reset {
+        Ok(value) if !state.is_full() => {
+          state.values.push(value);
+          None
+        },
+        Ok(value) | Err(value) => Some(value)
+      };
+      (state.release(), discarded)
+    };
+    drop(discarded);
+    // Notify the longest-waiting caller, if any.
+    if let Some(next) = next {
+      next.notify();
+    }
+  }
+
+  #[inline]
+  fn remove_or_create(&self) -> T {
+    self.take_extra_slot();
+    self.fill_slot()
+  }
+
+  #[inline]
+  fn on_detach(&self) {
+    self.release();
+  }
+}
@@ -0,0 +1,155 @@
+use std::cmp;
+
+/// A snapshot of a `Pool`'s counters, as returned by `Pool::stats`.
+///
+/// Counters are only updated while statistics collection is enabled (see
+/// `CollectStats`); otherwise they remain at zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PoolStats {
+  /// Checkouts that were served by a value already in the pool.
+  pub hits: usize,
+  /// Checkouts that found the pool empty and had to ask the `Supply` for a new value.
+  pub misses: usize,
+  /// Values that were returned to the pool and kept for reuse.
+  pub returns: usize,
+  /// Values that were dropped on return because the pool was full or out of `MaxRetainedBytes`.
+  pub discards: usize,
+  /// Values that were dropped because they failed validation.
+  pub rejected: usize,
+  /// Values that were dropped because resetting them panicked.
+  pub poisoned: usize,
+  /// Returned values whose capacity exceeded `MaxRetainedCapacity`. Depending on the
+  /// `OversizePolicy`, these were either shrunk and kept or dropped.
+  pub oversized: usize,
+  /// Idle values that were dropped for exceeding the `MaxIdleTime`.
+  pub evictions: usize,
+  /// Values that were detached from the pool and will not be returned.
+  pub detaches: usize,
+  /// Values that are currently checked out in a smartpointer.
+  pub outstanding: usize,
+  /// The largest number of values that have been checked out at once.
+  pub peak_outstanding: usize,
+  /// Returned values that are waiting to be reset under `ResetTiming::OnCheckout`
+  /// or `ResetTiming::Deferred`.
+  pub pending_resets: usize
+}
+
+/// The live counters behind `PoolStats`.
+pub(crate) struct Counters {
+  enabled: bool,
+  stats: PoolStats
+}
+
+impl Counters {
+  #[inline]
+  pub fn new(enabled: bool) -> Counters {
+    Counters { enabled, stats: PoolStats::default() }
+  }
+
+  #[inline]
+  pub fn snapshot(&self, pending_resets: usize) -> PoolStats {
+    if self.enabled {
+      PoolStats { pending_resets, ..self.stats }
+    } else {
+      self.stats
+    }
+  }
+
+  #[inline]
+  pub fn hit(&mut self) {
+    if self.enabled {
+      self.stats.hits += 1;
+    }
+  }
+
+  #[inline]
+  pub fn miss(&mut self) {
+    if self.enabled {
+      self.stats.misses += 1;
+    }
+  }
+
+  #[inline]
+  pub fn check_out(&mut self) {
+    if self.enabled {
+      self.stats.outstanding += 1;
+      self.stats.peak_outstanding = cmp::max(self.stats.peak_outstanding, self.stats.outstanding);
+    }
+  }
+
+  #[inline]
+  pub fn returned(&mut self) {
+    if self.enabled {
+      self.stats.returns += 1;
+      self.stats.outstanding -= 1;
+    }
+  }
+
+  /// Records a value that there was no room for. `was_outstanding` is false for
+  /// values that were already counted as returned before their deferred reset.
+  #[inline]
+  pub fn discarded(&mut self, was_outstanding: bool) {
+    if self.enabled {
+      self.stats.discards += 1;
+      if was_outstanding {
+        self.stats.outstanding -= 1;
+      }
+    }
+  }
+
+  /// Records a value that failed validation. `was_outstanding` is false for idle
+  /// values that were rejected during a checkout.
+  #[inline]
+  pub fn rejected(&mut self, was_outstanding: bool) {
+    if self.enabled {
+      self.stats.rejected += 1;
+      if was_outstanding {
+        self.stats.outstanding -= 1;
+      }
+    }
+  }
+
+  /// Records a value whose reset panicked. `was_outstanding` is false for values
+  /// that were already counted as returned before their deferred reset.
+  #[inline]
+  pub fn poisoned(&mut self, was_outstanding: bool) {
+    if self.enabled {
+      self.stats.poisoned += 1;
+      if was_outstanding {
+        self.stats.outstanding -= 1;
+      }
+    }
+  }
+
+  /// Records a returned value that exceeded `MaxRetainedCapacity`. If it was
+  /// `dropped`, it will not also be recorded as returned.
+  #[inline]
+  pub fn oversized(&mut self, dropped: bool) {
+    if self.enabled {
+      self.stats.oversized += 1;
+      if dropped {
+        self.stats.outstanding -= 1;
+      }
+    }
+  }
+
+  /// Records an idle value that was dropped for exceeding the `MaxIdleTime`.
+  #[inline]
+  pub fn evicted(&mut self) {
+    if self.enabled {
+      self.stats.evictions += 1;
+    }
+  }
+
+  /// Records a value leaving the pool for good. `was_outstanding` is false for
+  /// values taken directly with `Pool::detached`, which were never checked out.
+  #[inline]
+  pub fn detached(&mut self, was_outstanding: bool) {
+    if self.enabled {
+      self.stats.detaches += 1;
+      if was_outstanding {
+        self.stats.outstanding -= 1;
+      }
+    }
+  }
+}
@@ -0,0 +1,291 @@
+// `Recycleable` and `InitializeWith` impls for std types beyond `String` and `Vec<T>`.
+// Each `reset` clears the value without giving up its allocated capacity. Composite
+// types like tuples and arrays are reset and initialized element by element.
+
+use std::array;
+use std::collections::{HashMap, HashSet, BTreeMap, BTreeSet, BinaryHeap, LinkedList, VecDeque};
+use std::ffi::{OsStr, OsString};
+use std::hash::{BuildHasher, Hash};
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use ::{Recycleable, InitializeWith};
+
+impl <K, V, S> Recycleable for HashMap<K, V, S> where S: BuildHasher + Default {
+  #[inline]
+  fn new() -> HashMap<K, V, S> {
+    HashMap::default()
+  }
+  #[inline]
+  fn reset(&mut self) {
+    self.clear();
+  }
+}
+
+impl <T, S> Recycleable for HashSet<T, S> where S: BuildHasher + Default {
+  #[inline]
+  fn new() -> HashSet<T, S> {
+    HashSet::default()
+  }
+  #[inline]
+  fn reset(&mut self) {
+    self.clear();
+  }
+}
+
+impl <K, V> Recycleable for BTreeMap<K, V> {
+  #[inline]
+  fn new() -> BTreeMap<K, V> {
+    BTreeMap::new()
+  }
+  #[inline]
+  fn reset(&mut self) {
+    self.clear();
+  }
+}
+
+impl <T> Recycleable for BTreeSet<T> {
+  #[inline]
+  fn new() -> BTreeSet<T> {
+    BTreeSet::new()
+  }
+  #[inline]
+  fn reset(&mut self) {
+    self.clear();
+  }
+}
+
+impl <T> Recycleable for BinaryHeap<T> where T: Ord {
+  #[inline]
+  fn new() -> BinaryHeap<T> {
+    BinaryHeap::new()
+  }
+  #[inline]
+  fn reset(&mut self) {
+    self.clear();
+  }
+}
+
+impl <T> Recycleable for LinkedList<T> {
+  #[inline]
+  fn new() -> LinkedList<T> {
+    LinkedList::new()
+  }
+  #[inline]
+  fn reset(&mut self) {
+    self.clear();
+  }
+}
+
+impl Recycleable for PathBuf {
+  #[inline]
+  fn new() -> PathBuf {
+    PathBuf::new()
+  }
+  #[inline]
+  fn reset(&mut self) {
+    self.as_mut_os_string().clear();
+  }
+}
+
+impl Recycleable for OsString {
+  #[inline]
+  fn new() -> OsString {
+    OsString::new()
+  }
+  #[inline]
+  fn reset(&mut self) {
+    self.clear();
+  }
+}
+
+impl Recycleable for Cursor<Vec<u8>> {
+  #[inline]
+  fn new() -> Cursor<Vec<u8>> {
+    Cursor::new(Vec::new())
+  }
+  #[inline]
+  fn reset(&mut self) {
+    self.get_mut().clear();
+    self.set_position(0);
+  }
+}
+
+impl <I, T> InitializeWith<I> for VecDeque<T> where I: Iterator<Item=T> {
+  #[inline]
+  fn initialize_with(&mut self, source: I) {
+    self.extend(source);
+  }
+}
+
+impl <I, K, V, S> InitializeWith<I> for HashMap<K, V, S> where
+  I: Iterator<Item=(K, V)>,
+  K: Eq + Hash,
+  S: BuildHasher {
+  #[inline]
+  fn initialize_with(&mut self, source: I) {
+    self.extend(source);
+  }
+}
+
+impl <I, T, S> InitializeWith<I> for HashSet<T, S> where
+  I: Iterator<Item=T>,
+  T: Eq + Hash,
+  S: BuildHasher {
+  #[inline]
+  fn initialize_with(&mut self, source: I) {
+    self.extend(source);
+  }
+}
+
+impl <I, K, V> InitializeWith<I> for BTreeMap<K, V> where I: Iterator<Item=(K, V)>, K: Ord {
+  #[inline]
+  fn initialize_with(&mut self, source: I) {
+    self.extend(source);
+  }
+}
+
+impl <I, T> InitializeWith<I> for BTreeSet<T> where I: Iterator<Item=T>, T: Ord {
+  #[inline]
+  fn initialize_with(&mut self, source: I) {
+    self.extend(source);
+  }
+}
+
+impl <I, T> InitializeWith<I> for BinaryHeap<T> where I: Iterator<Item=T>, T: Ord {
+  #[inline]
+  fn initialize_with(&mut self, source: I) {
+    self.extend(source);
+  }
+}
+
+impl <I, T> InitializeWith<I> for LinkedList<T> where I: Iterator<Item=T> {
+  #[inline]
+  fn initialize_with(&mut self, source: I) {
+    self.extend(source);
+  }
+}
+
+impl <A> InitializeWith<A> for PathBuf where A: AsRef<Path> {
+  #[inline]
+  fn initialize_with(&mut self, source: A) {
+    self.push(source);
+  }
+}
+
+impl <A> InitializeWith<A> for OsString where A: AsRef<OsStr> {
+  #[inline]
+  fn initialize_with(&mut self, source: A) {
+    self.push(source);
+  }
+}
+
+impl <A> InitializeWith<A> for Cursor<Vec<u8>> where A: AsRef<[u8]> {
+  #[inline]
+  fn initialize_with(&mut self, source: A) {
+    self.get_mut().extend_from_slice(source.as_ref());
+  }
+}
+
+impl <T> Recycleable for Box<T> where T: Recycleable {
+  #[inline]
+  fn new() -> Box<T> {
+    Box::new(T::new())
+  }
+  #[inline]
+  fn reset(&mut self) {
+    (**self).reset();
+  }
+}
+
+/// A fresh `Option` is `None`. Resetting a `Some` resets the value it holds rather
+/// than dropping it, so that its allocation can be reused.
+impl <T> Recycleable for Option<T> where T: Recycleable {
+  #[inline]
+  fn new() -> Option<T> {
+    None
+  }
+  #[inline]
+  fn reset(&mut self) {
+    if let Some(value) = self {
+      value.reset();
+    }
+  }
+}
+
+impl <T, const N: usize> Recycleable for [T; N] where T: Recycleable {
+  #[inline]
+  fn new() -> [T; N] {
+    array::from_fn(|_| T::new())
+  }
+  #[inline]
+  fn reset(&mut self) {
+    for value in self.iter_mut() {
+      value.reset();
+    }
+  }
+}
+
+impl <S, T> InitializeWith<S> for Box<T> where T: InitializeWith<S> {
+  #[inline]
+  fn initialize_with(&mut self, source: S) {
+    (**self).initialize_with(source);
+  }
+}
+
+/// Initializes the value held by a `Some`, or a new one if the `Option` is `None`.
+impl <S, T> InitializeWith<S> for Option<T> where T: InitializeWith<S> + Recycleable {
+  #[inline]
+  fn initialize_with(&mut self, source: S) {
+    self.get_or_insert_with(T::new).initialize_with(source);
+  }
+}
+
+impl <S, T, const N: usize> InitializeWith<[S; N]> for [T; N] where T: InitializeWith<S> {
+  #[inline]
+  fn initialize_with(&mut self, source: [S; N]) {
+    for (value, source) in self.iter_mut().zip(IntoIterator::into_iter(source)) {
+      value.initialize_with(source);
+    }
+  }
+}
+
+macro_rules! impl_tuple {
+  ($(($($element:ident $source:ident $index:tt),+))+) => {
+    $(
+      impl <$($element),+> Recycleable for ($($element,)+) where $($element: Recycleable),+ {
+        #[inline]
+        fn new() -> ($($element,)+) {
+          ($(<$element as Recycleable>::new(),)+)
+        }
+        #[inline]
+        fn reset(&mut self) {
+          $(self.$index.reset();)+
+        }
+      }
+
+      impl <$($element, $source),+> InitializeWith<($($source,)+)> for ($($element,)+) where
+        $($element: InitializeWith<$source>),+ {
+        #[inline]
+        fn initialize_with(&mut self, source: ($($source,)+)) {
+          $(self.$index.initialize_with(source.$index);)+
+        }
+      }
+    )+
+  }
+}
+
+impl_tuple! {
+  (A SA 0)
+  (A SA 0, B SB 1)
+  (A SA 0, B SB 1, C SC 2)
+  (A SA 0, B SB 1, C SC 2, D SD 3)
+  (A SA 0, B SB 1, C SC 2, D SD 3, E SE 4)
+  (A SA 0, B SB 1, C SC 2, D SD 3, E SE 4, F SF 5)
+  (A SA 0, B SB 1, C SC 2, D SD 3, E SE 4, F SF 5, G SG 6)
+  (A SA 0, B SB 1, C SC 2, D SD 3, E SE 4, F SF 5, G SG 6, H SH 7)
+  (A SA 0, B SB 1, C SC 2, D SD 3, E SE 4, F SF 5, G SG 6, H SH 7, I SI 8)
+  (A SA 0, B SB 1, C SC 2, D SD 3, E SE 4, F SF 5, G SG 6, H SH 7, I SI 8, J SJ 9)
+  (A SA 0, B SB 1, C SC 2, D SD 3, E SE 4, F SF 5, G SG 6, H SH 7, I SI 8, J SJ 9, K SK 10)
+  (A SA 0, B SB 1, C SC 2, D SD 3, E SE 4, F SF 5, G SG 6, H SH 7, I SI 8, J SJ 9, K SK 10, L SL 11)
+}
@@ -0,0 +1,281 @@
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use std::thread::{self, Thread};
+use std::time::{Duration, Instant};
+
+use ::{InitializeWith, OptionSetter, PoolError, Recycleable, RecycledInner, Supply};
+use slots::Slots;
+
+/// A smartpointer which uses a shared reference (`&`) to know
+/// when to move its wrapped value back to the `SyncPool` that
+/// issued it. It can be sent to and dropped on another thread.
+///
+/// Cloning a `SyncRecycled` checks out an additional value even if the pool's
+/// `MaxOutstanding` limit has already been reached, and without waiting in line
+/// behind blocked threads. The clone counts toward the limit until it's returned.
+pub struct SyncRecycled<'a, T: 'a> where T: Recycleable {
+  value: RecycledInner<&'a SyncValues<T>, T>
+}
+
+/// A smartpointer which uses atomic reference counting (`Arc`) to know
+/// when to move its wrapped value back to the `SyncPool` that
+/// issued it. It can be sent to and dropped on another thread.
+///
+/// Like `SyncRecycled`, cloning an `ArcRecycled` is exempt from `MaxOutstanding`.
+pub struct ArcRecycled<T> where T: Recycleable {
+  value: RecycledInner<Arc<SyncValues<T>>, T>
+}
+
+impl_recycled!{ ArcRecycled, ArcRecycled<T>, Arc<SyncValues<T>> }
+impl_recycled!{ SyncRecycled, SyncRecycled<'a, T>, &'a SyncValues<T> }
+
+/// The storage shared by a `SyncPool` and its smartpointers. Waiting threads are unparked
+/// when a slot is handed to them.
+type SyncValues<T> = Slots<T, Thread>;
+
+/// A thread-safe collection of values that can be reused without requiring new allocations.
+///
+/// `SyncPool` can be shared between threads (for example, behind an `Arc`). The smartpointers
+/// it issues can be sent to other threads; when one goes out of scope, the wrapped value is
+/// returned to the pool regardless of which thread dropped it.
+pub struct SyncPool <T> where T : Recycleable {
+  values: Arc<SyncValues<T>>,
+}
+
+impl <T> SyncPool <T> where T: Recycleable {
+
+  /// Creates a pool with `size` elements of type `T` allocated.
+  #[inline]
+  pub fn with_size(size: usize) -> SyncPool <T> {
+    SyncPool::with_size_and_max(size, usize::MAX)
+  }
+
+  /// Creates a pool with `size` elements of type `T` allocated
+  /// and sets a maximum pool size of `max_size`. Values being
+  /// added to the pool via `SyncPool::attach` or being returned to
+  /// the pool upon dropping will instead be discarded if the pool
+  /// is full.
+  #[inline]
+  pub fn with_size_and_max(starting_size: usize, max_size: usize) -> SyncPool <T> {
+    let supplier = Box::new(|| T::new());
+    let values: SyncValues<T> = Slots::new(supplier, starting_size, max_size, usize::MAX);
+    SyncPool {
+      values: Arc::new(values)
+    }
+  }
+
+  /// Returns the number of values remaining in the pool.
+  #[inline]
+  pub fn size(&self) -> usize {
+    self.values.len()
+  }
+
+  /// Returns the maximum number of values the pool can hold.
+  #[inline]
+  pub fn max_size(&self) -> usize {
+    self.values.cap()
+  }
+
+  /// Returns the number of values that are currently checked out.
+  #[inline]
+  pub fn outstanding(&self) -> usize {
+    self.values.outstanding()
+  }
+
+  /// Returns the maximum number of values that can be checked out at once.
+  #[inline]
+  pub fn max_outstanding(&self) -> usize {
+    self.values.max_outstanding()
+  }
+
+  /// Returns the number of threads waiting for a value to be returned.
+  #[inline]
+  pub fn waiting(&self) -> usize {
+    self.values.waiting()
+  }
+
+  /// Removes a value from the pool and returns it wrapped in
+  /// a `SyncRecycled` smartpointer. If the pool is empty when the
+  /// method is called, a new value will be allocated. If the limit
+  /// on outstanding values has been reached, the calling thread is
+  /// blocked until a value is returned.
+  #[inline]
+  #[allow(clippy::new_ret_no_self)]
+  pub fn new(&self) -> SyncRecycled<'_, T> {
+    let t = self.wait_for_value();
+    SyncRecycled::new(&self.values, t)
+  }
+
+  /// Removes a value from the pool, initializes it using the provided
+  /// source value, and returns it wrapped in a `SyncRecycled` smartpointer.
+  /// If the pool is empty when the method is called, a new value will be
+  /// allocated. If the limit on outstanding values has been reached, the
+  /// calling thread is blocked until a value is returned.
+  #[inline(always)]
+  pub fn new_from<A>(&self, source: A) -> SyncRecycled<'_, T> where T: InitializeWith<A> {
+    let t = self.wait_for_value();
+    SyncRecycled::new_from(&self.values, t, source)
+  }
+
+  /// Removes a value from the pool and returns it wrapped in a `SyncRecycled`
+  /// smartpointer without blocking. Returns `Err(PoolError::Exhausted)` if the
+  /// limit on outstanding values has been reached or other threads are waiting.
+  #[inline]
+  pub fn try_get(&self) -> Result<SyncRecycled<'_, T>, PoolError> {
+    let t = self.checkout(false, None)?;
+    Ok(SyncRecycled::new(&self.values, t))
+  }
+
+  /// Removes a value from the pool and returns it wrapped in a `SyncRecycled`
+  /// smartpointer. If the limit on outstanding values has been reached, the
+  /// calling thread is parked until a value is returned or until `timeout`
+  /// has elapsed, in which case `Err(PoolError::TimedOut)` is returned.
+  /// Waiting threads are served in the order in which they started waiting.
+  /// A timeout too long to be represented, like `Duration::MAX`, never elapses.
+  #[inline]
+  pub fn get_timeout(&self, timeout: Duration) -> Result<SyncRecycled<'_, T>, PoolError> {
+    let t = self.checkout(true, Instant::now().checked_add(timeout))?;
+    Ok(SyncRecycled::new(&self.values, t))
+  }
+
+  /// Associates the provided value with the pool by wrapping it in a
+  /// `SyncRecycled` smartpointer. The value counts towards the limit on
+  /// outstanding values even if that limit has already been reached.
+  #[inline]
+  pub fn attach(&self, value: T) -> SyncRecycled<'_, T> {
+    self.values.take_extra_slot();
+    SyncRecycled::new(&self.values, value)
+  }
+
+  /// Removes a value from the pool and returns it without wrapping it in
+  /// a smartpointer. When the value goes out of scope it will not be
+  /// returned to the pool. Detached values do not count towards the limit
+  /// on outstanding values.
+  #[inline]
+  pub fn detached(&self) -> T {
+    self.values.remove_or_supply()
+  }
+
+  /// Removes a value from the pool and returns it wrapped in
+  /// an `ArcRecycled` smartpointer. If the pool is empty when the
+  /// method is called, a new value will be allocated. If the limit
+  /// on outstanding values has been reached, the calling thread is
+  /// blocked until a value is returned.
+  #[inline]
+  pub fn new_arc(&self) -> ArcRecycled<T> {
+    let t = self.wait_for_value();
+    let pool_reference = self.values.clone();
+    ArcRecycled::new(pool_reference, t)
+  }
+
+  /// Removes a value from the pool, initializes it using the provided
+  /// source value, and returns it wrapped in an `ArcRecycled` smartpointer.
+  /// If the pool is empty when the method is called, a new value will be
+  /// allocated. If the limit on outstanding values has been reached, the
+  /// calling thread is blocked until a value is returned.
+  #[inline(always)]
+  pub fn new_arc_from<A>(&self, source: A) -> ArcRecycled<T> where T: InitializeWith<A> {
+    let t = self.wait_for_value();
+    let pool_reference = self.values.clone();
+    ArcRecycled::new_from(pool_reference, t, source)
+  }
+
+  /// Associates the provided value with the pool by wrapping it in an
+  /// `ArcRecycled` smartpointer. The value counts towards the limit on
+  /// outstanding values even if that limit has already been reached.
+  #[inline]
+  pub fn attach_arc(&self, value: T) -> ArcRecycled<T> {
+    self.values.take_extra_slot();
+    let pool_reference = self.values.clone();
+    ArcRecycled::new(pool_reference, value)
+  }
+
+  #[inline]
+  fn wait_for_value(&self) -> T {
+    self.checkout(true, None).expect("Waiting without a deadline cannot time out.")
+  }
+
+  /// Takes a slot and a value to go in it. If no slot is free and `wait` is set, the calling
+  /// thread joins the back of the line and parks until a slot is handed to it or `deadline` passes.
+  fn checkout(&self, wait: bool, deadline: Option<Instant>) -> Result<T, PoolError> {
+    if !wait {
+      if !self.values.try_take_slot() {
+        return Err(PoolError::Exhausted);
+      }
+      return Ok(self.values.fill_slot());
+    }
+    let id = match self.values.take_slot_or_join(thread::current()) {
+      Some(id) => id,
+      None => return Ok(self.values.fill_slot())
+    };
+    loop {
+      match deadline {
+        Some(deadline) => thread::park_timeout(deadline.saturating_duration_since(Instant::now())),
+        None => thread::park()
+      }
+      if self.values.take_granted(id, None) {
+        return Ok(self.values.fill_slot());
+      }
+      if let Some(deadline) = deadline {
+        if Instant::now() >= deadline {
+          // A slot handed over in the meantime is passed along to the next thread.
+          self.values.leave(id);
+          return Err(PoolError::TimedOut);
+        }
+      }
+    }
+  }
+}
+
+/// Produces a `SyncPoolBuilder` instance
+///
+/// # Example
+///
+/// ```
+/// extern crate lifeguard;
+/// use lifeguard::*;
+///
+/// fn main() {
+///   let pool: SyncPool<String> = sync_pool()
+///     .with(StartingSize(128))
+///     .with(MaxSize(4096))
+///     .with(Supplier(|| String::with_capacity(1024)))
+///     .build();
+/// }
+/// ```
+pub fn sync_pool<T>() -> SyncPoolBuilder<T> where T: Recycleable {
+  SyncPoolBuilder {
+    starting_size: 16,
+    max_size: usize::MAX,
+    max_outstanding: usize::MAX,
+    supplier: None
+  }
+}
+
+/// Used to define settings for and ultimately create a `SyncPool`. It accepts
+/// the same settings as `PoolBuilder`, but its `Supplier` must be `Send`.
+pub struct SyncPoolBuilder<T> where T: Recycleable {
+  pub starting_size: usize,
+  pub max_size: usize,
+  pub max_outstanding: usize,
+  pub supplier: Option<Box<dyn Supply<Output=T> + Send>>,
+}
+
+impl <T> SyncPoolBuilder<T> where T: Recycleable {
+  pub fn with<U>(self, option_setter: U) -> SyncPoolBuilder<T> where
+      U: OptionSetter<SyncPoolBuilder<T>> {
+    option_setter.set_option(self)
+  }
+
+  pub fn build(self) -> SyncPool<T> {
+    let supplier = self.supplier.unwrap_or(Box::new(|| T::new()));
+    let values: SyncValues<T> =
+      Slots::new(supplier, self.starting_size, self.max_size, self.max_outstanding);
+    SyncPool {
+      values: Arc::new(values)
+    }
+  }
+}
@@ -0,0 +1,376 @@
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{self, AtomicUsize};
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+
+use ::{try_reset, InitializeWith, OptionSetter, Recycleable, RecycledInner, Recycler, Supply};
+
+/// A smartpointer which uses a shared reference (`&`) to know when to move its
+/// wrapped value back to the `TieredPool` that issued it. When it is dropped, the
+/// value is returned to the local cache of whichever thread dropped it.
+pub struct TieredRecycled<'a, T: 'a> where T: Recycleable + Send + 'static {
+  value: RecycledInner<&'a Arc<Depot<T>>, T>
+}
+
+impl_recycled!{ TieredRecycled, TieredRecycled<'a, T>, &'a Arc<Depot<T>>, Recycleable + Send + 'static }
+
+static NEXT_DEPOT_ID: AtomicUsize = AtomicUsize::new(0);
+// Bumped whenever a depot is freed so that every thread knows to sweep its orphaned magazines.
+static DROPPED_DEPOTS: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+  // Each thread keeps one magazine for every `TieredPool` it has touched.
+  static MAGAZINES: RefCell<Vec<Box<dyn LocalCache>>> = RefCell::new(Vec::new());
+  // The value of `DROPPED_DEPOTS` when this thread last swept its magazines.
+  static SWEPT_AT: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Type-erased view of a `Magazine<T>` so that magazines for pools of different
+/// types can share the same thread-local list.
+trait LocalCache {
+  fn depot_id(&self) -> usize;
+  fn is_orphaned(&self) -> bool;
+  fn as_any(&mut self) -> &mut dyn Any;
+}
+
+/// A thread's private stack of idle values for a single pool.
+struct Magazine<T> where T: Recycleable + Send + 'static {
+  depot_id: usize,
+  depot: Weak<Depot<T>>,
+  values: Vec<T>
+}
+
+impl <T> LocalCache for Magazine<T> where T: Recycleable + Send + 'static {
+  fn depot_id(&self) -> usize {
+    self.depot_id
+  }
+
+  fn is_orphaned(&self) -> bool {
+    self.depot.strong_count() == 0
+  }
+
+  fn as_any(&mut self) -> &mut dyn Any {
+    self
+  }
+}
+
+impl <T> Drop for Magazine<T> where T: Recycleable + Send + 'static {
+  fn drop(&mut self) {
+    // When a thread exits, hand its idle values back to the depot if the pool still exists.
+    if let Some(depot) = self.depot.upgrade() {
+      depot.store_batch(mem::take(&mut self.values));
+    }
+  }
+}
+
+/// Batches of idle values shared by every thread using a `TieredPool`.
+struct DepotStorage<T> {
+  batches: Vec<Vec<T>>,
+  len: usize
+}
+
+/// The shared tier of a `TieredPool`. Threads only lock it to exchange whole
+/// batches of values or to allocate new ones.
+struct Depot<T> where T: Recycleable + Send + 'static {
+  id: usize,
+  batch_size: usize,
+  capacity: usize,
+  storage: Mutex<DepotStorage<T>>,
+  supplier: Mutex<Box<dyn Supply<Output=T> + Send>>
+}
+
+impl <T> Depot<T> where T: Recycleable + Send + 'static {
+  fn new(mut supplier: Box<dyn Supply<Output=T> + Send>, starting_size: usize,
+         batch_size: usize, capacity: usize) -> Depot<T> {
+    use std::cmp;
+    let batch_size = cmp::max(batch_size, 1);
+    let starting_size = cmp::min(starting_size, capacity);
+    let mut batches = Vec::new();
+    let mut remaining = starting_size;
+    while remaining > 0 {
+      let count = cmp::min(remaining, batch_size);
+      batches.push((0..count).map(|_| supplier.get()).collect());
+      remaining -= count;
+    }
+    Depot {
+      id: NEXT_DEPOT_ID.fetch_add(1, atomic::Ordering::Relaxed),
+      batch_size,
+      capacity,
+      storage: Mutex::new(DepotStorage { batches, len: starting_size }),
+      supplier: Mutex::new(supplier)
+    }
+  }
+
+  #[inline]
+  fn storage(&self) -> MutexGuard<'_, DepotStorage<T>> {
+    self.storage.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+  }
+
+  #[inline]
+  fn supply(&self) -> T {
+    self.supplier.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).get()
+  }
+
+  fn take_batch(&self) -> Option<Vec<T>> {
+    let mut storage = self.storage();
+    let batch = storage.batches.pop();
+    if let Some(ref batch) = batch {
+      storage.len -= batch.len();
+    }
+    batch
+  }
+
+  /// Stores as much of `batch` as the depot has room for. Anything left over is
+  /// dropped after the lock has been released.
+  fn store_batch(&self, mut batch: Vec<T>) {
+    if batch.is_empty() {
+      return;
+    }
+    let excess = {
+      let mut storage = self.storage();
+      let room = self.capacity - storage.len;
+      let excess = if batch.len() > room { batch.split_off(room) } else { Vec::new() };
+      if !batch.is_empty() {
+        storage.len += batch.len();
+        storage.batches.push(batch);
+      }
+      excess
+    };
+    drop(excess);
+  }
+}
+
+impl <T> Drop for Depot<T> where T: Recycleable + Send + 'static {
+  fn drop(&mut self) {
+    // By now no strong references remain, so any thread that sees the new count
+    // will also see this depot's magazines as orphaned.
+    DROPPED_DEPOTS.fetch_add(1, atomic::Ordering::Release);
+  }
+}
+
+/// Runs `f` against the calling thread's magazine for `depot`, creating it if necessary.
+/// Returns `None` without running `f` if the magazine cannot be reached, either because
+/// the thread is shutting down or because this is a reentrant call from within `f`'s caller.
+fn with_magazine<T, F, R>(depot: &Arc<Depot<T>>, f: F) -> Option<R> where
+    T: Recycleable + Send + 'static,
+    F: FnOnce(&mut Vec<T>) -> R {
+  MAGAZINES.try_with(|magazines| {
+    let mut magazines = match magazines.try_borrow_mut() {
+      Ok(magazines) => magazines,
+      Err(_) => return None
+    };
+    // Free the values cached for pools that have been dropped since the last sweep.
+    let dropped = DROPPED_DEPOTS.load(atomic::Ordering::Acquire);
+    let stale = SWEPT_AT.try_with(|swept_at| swept_at.replace(dropped) != dropped).unwrap_or(false);
+    if stale {
+      magazines.retain(|m| !m.is_orphaned());
+    }
+    let index = match magazines.iter().position(|m| m.depot_id() == depot.id) {
+      Some(index) => index,
+      None => {
+        magazines.push(Box::new(Magazine {
+          depot_id: depot.id,
+          depot: Arc::downgrade(depot),
+          values: Vec::with_capacity(depot.batch_size * 2)
+        }));
+        magazines.len() - 1
+      }
+    };
+    let magazine = magazines[index].as_any().downcast_mut::<Magazine<T>>()
+      .expect("Magazine type did not match its depot.");
+    Some(f(&mut magazine.values))
+  }).ok().and_then(|result| result)
+}
+
+impl <T> Recycler<T> for Arc<Depot<T>> where T: Recycleable + Send + 'static {
+  fn recycle(&self, value: T) {
+    let value = match try_reset(value, T::reset) {
+      Ok(value) => value,
+      Err(_) => return
+    };
+    let batch_size = self.batch_size;
+    let mut value = Some(value);
+    let overflow = with_magazine(self, |magazine| {
+      magazine.push(value.take().unwrap());
+      // Once the local stack holds two batches, move one of them to the depot.
+      if magazine.len() >= batch_size * 2 {
+        let split_at = magazine.len() - batch_size;
+        Some(magazine.split_off(split_at))
+      } else {
+        None
+      }
+    });
+    if let Some(Some(batch)) = overflow {
+      self.store_batch(batch);
+    }
+    // The local stack was unreachable; return the value to the depot directly.
+    if let Some(value) = value {
+      self.store_batch(vec![value]);
+    }
+  }
+
+  fn remove_or_create(&self) -> T {
+    if let Some(Some(value)) = with_magazine(self, |magazine| magazine.pop()) {
+      return value;
+    }
+    // The local stack is empty; refill it with a batch from the depot.
+    if let Some(mut batch) = self.take_batch() {
+      let value = batch.pop().expect("Depot batches are never empty.");
+      with_magazine(self, |magazine| magazine.append(&mut batch));
+      // If the local stack was unreachable, the batch goes back to the depot untouched.
+      self.store_batch(batch);
+      return value;
+    }
+    self.supply()
+  }
+}
+
+/// A thread-safe pool in which each thread keeps a small local cache of idle values.
+///
+/// Checkouts and returns are served from the calling thread's cache whenever possible.
+/// Only when a cache runs empty or overflows does the thread lock the shared depot, and
+/// then it exchanges a whole batch of `LocalBatchSize` values at once. The depot will
+/// hold at most `DepotCapacity` idle values; values beyond that are dropped.
+///
+/// Values cached by a thread are handed back to the depot when that thread exits.
+/// Once the pool itself is dropped, each thread frees its cached values the next
+/// time it uses any `TieredPool`, or when it exits.
+pub struct TieredPool <T> where T: Recycleable + Send + 'static {
+  depot: Arc<Depot<T>>
+}
+
+impl <T> TieredPool <T> where T: Recycleable + Send + 'static {
+
+  /// Returns the number of idle values in the shared depot. Values held
+  /// in the local caches of individual threads are not included.
+  #[inline]
+  pub fn size(&self) -> usize {
+    self.depot.storage().len
+  }
+
+  /// Returns the number of idle values in the calling thread's local cache.
+  #[inline]
+  pub fn local_size(&self) -> usize {
+    with_magazine(&self.depot, |magazine| magazine.len()).unwrap_or(0)
+  }
+
+  /// Returns the number of values moved between a local cache and the depot at once.
+  #[inline]
+  pub fn batch_size(&self) -> usize {
+    self.depot.batch_size
+  }
+
+  /// Returns the maximum number of values the depot can hold.
+  #[inline]
+  pub fn depot_capacity(&self) -> usize {
+    self.depot.capacity
+  }
+
+  /// Removes a value from the pool and returns it wrapped in
+  /// a `TieredRecycled` smartpointer. If the pool is empty when the
+  /// method is called, a new value will be allocated.
+  #[inline]
+  #[allow(clippy::new_ret_no_self)]
+  pub fn new(&self) -> TieredRecycled<'_, T> {
+    let t = self.detached();
+    TieredRecycled::new(&self.depot, t)
+  }
+
+  /// Removes a value from the pool, initializes it using the provided
+  /// source value, and returns it wrapped in a `TieredRecycled` smartpointer.
+  /// If the pool is empty when the method is called, a new value will be
+  /// allocated.
+  #[inline(always)]
+  pub fn new_from<A>(&self, source: A) -> TieredRecycled<'_, T> where T: InitializeWith<A> {
+    let t = self.detached();
+    TieredRecycled::new_from(&self.depot, t, source)
+  }
+
+  /// Associates the provided value with the pool by wrapping it in a
+  /// `TieredRecycled` smartpointer.
+  #[inline]
+  pub fn attach(&self, value: T) -> TieredRecycled<'_, T> {
+    TieredRecycled::new(&self.depot, value)
+  }
+
+  /// Removes a value from the pool and returns it without wrapping it in
+  /// a smartpointer. When the value goes out of scope it will not be
+  /// returned to the pool.
+  #[inline]
+  pub fn detached(&self) -> T {
+    self.depot.remove_or_create()
+  }
+}
+
+impl <T> Drop for TieredPool<T> where T: Recycleable + Send + 'static {
+  fn drop(&mut self) {
+    // Free the calling thread's cache right away; other threads will discard
+    // theirs the next time they use any `TieredPool` or when they exit.
+    let id = self.depot.id;
+    let magazine = MAGAZINES.try_with(|magazines| {
+      let mut magazines = magazines.try_borrow_mut().ok()?;
+      let index = magazines.iter().position(|m| m.depot_id() == id)?;
+      Some(magazines.swap_remove(index))
+    });
+    if let Ok(Some(mut magazine)) = magazine {
+      // Don't bother returning its values to a depot that's about to be dropped.
+      if let Some(magazine) = magazine.as_any().downcast_mut::<Magazine<T>>() {
+        magazine.depot = Weak::new();
+      }
+    }
+  }
+}
+
+/// Produces a `TieredPoolBuilder` instance
+///
+/// # Example
+///
+/// ```
+/// extern crate lifeguard;
+/// use lifeguard::*;
+///
+/// fn main() {
+///   let pool: TieredPool<String> = tiered_pool()
+///     .with(StartingSize(128))
+///     .with(LocalBatchSize(16))
+///     .with(DepotCapacity(4096))
+///     .with(Supplier(|| String::with_capacity(1024)))
+///     .build();
+/// }
+/// ```
+pub fn tiered_pool<T>() -> TieredPoolBuilder<T> where T: Recycleable + Send + 'static {
+  TieredPoolBuilder {
+    starting_size: 16,
+    batch_size: 32,
+    depot_capacity: usize::MAX,
+    supplier: None
+  }
+}
+
+/// Used to define settings for and ultimately create a `TieredPool`.
+pub struct TieredPoolBuilder<T> where T: Recycleable + Send + 'static {
+  pub starting_size: usize,
+  pub batch_size: usize,
+  pub depot_capacity: usize,
+  pub supplier: Option<Box<dyn Supply<Output=T> + Send>>,
+}
+
+impl <T> TieredPoolBuilder<T> where T: Recycleable + Send + 'static {
+  pub fn with<U>(self, option_setter: U) -> TieredPoolBuilder<T> where
+      U: OptionSetter<TieredPoolBuilder<T>> {
+    option_setter.set_option(self)
+  }
+
+  pub fn build(self) -> TieredPool<T> {
+    let supplier = self.supplier.unwrap_or(Box::new(|| T::new()));
+    let depot = Depot::new(supplier, self.starting_size, self.batch_size, self.depot_capacity);
+    TieredPool {
+      depot: Arc::new(depot)
+    }
+  }
+}
@@ -0,0 +1,73 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fmt;
+use std::panic::Location;
+use std::time::{Duration, Instant};
+
+/// The smartpointers checked out from a single call site that have been
+/// outstanding for longer than the threshold given to `Pool::outstanding_report`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutstandingCheckouts {
+  /// Where the values were checked out.
+  pub location: &'static Location<'static>,
+  /// How many of them are still checked out.
+  pub count: usize,
+  /// How long the oldest of them has been checked out.
+  pub oldest: Duration
+}
+
+impl fmt::Display for OutstandingCheckouts {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{} checked out at {}, the oldest for {:?}", self.count, self.location, self.oldest)
+  }
+}
+
+/// Records where and when each outstanding smartpointer was checked out.
+/// See `TrackCheckouts`.
+pub(crate) struct CheckoutTracker {
+  next_ticket: usize,
+  checkouts: HashMap<usize, (&'static Location<'static>, Instant)>
+}
+
+impl CheckoutTracker {
+  #[inline]
+  pub fn new() -> CheckoutTracker {
+    CheckoutTracker { next_ticket: 0, checkouts: HashMap::new() }
+  }
+
+  /// Records a checkout and returns the ticket that will end it.
+  #[inline]
+  pub fn track(&mut self, location: &'static Location<'static>, now: Instant) -> usize {
+    let ticket = self.next_ticket;
+    self.next_ticket = self.next_ticket.wrapping_add(1);
+    self.checkouts.insert(ticket, (location, now));
+    ticket
+  }
+
+  #[inline]
+  pub fn untrack(&mut self, ticket: usize) {
+    self.checkouts.remove(&ticket);
+  }
+
+  /// Groups the checkouts that are at least `older_than` by call site, with
+  /// the sites holding the most values first.
+  pub fn report(&self, now: Instant, older_than: Duration) -> Vec<OutstandingCheckouts> {
+    let mut sites: HashMap<&'static Location<'static>, OutstandingCheckouts> = HashMap::new();
+    for &(location, since) in self.checkouts.values() {
+      let age = now.saturating_duration_since(since);
+      if age < older_than {
+        continue;
+      }
+      let site = sites.entry(location).or_insert(OutstandingCheckouts {
+        location,
+        count: 0,
+        oldest: age
+      });
+      site.count += 1;
+      site.oldest = site.oldest.max(age);
+    }
+    let mut report: Vec<OutstandingCheckouts> = sites.into_values().collect();
+    report.sort_by_key(|site| (Reverse(site.count), Reverse(site.oldest), site.location));
+    report
+  }
+}
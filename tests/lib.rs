@@ -194,4 +194,1636 @@ mod tests {
             assert_eq!(3, vec3.len())
         }
     }
+
+  #[test]
+  fn test_stats() {
+    let str_pool : Pool<String> = Pool::with_size_and_max(1, 2);
+    {
+      let _a = str_pool.new_from("cat");
+      let _b = str_pool.new_rc_from("dog");
+      let _c = str_pool.attach("mouse".to_owned());
+      let stats = str_pool.stats();
+      assert_eq!(1, stats.hits);
+      assert_eq!(1, stats.misses);
+      assert_eq!(3, stats.outstanding);
+      let _d : String = str_pool.new().detach();
+    }
+    let stats = str_pool.stats();
+    assert_eq!(PoolStats {
+      hits: 1,
+      misses: 2,
+      returns: 2,
+      discards: 1,
+      rejected: 0,
+      poisoned: 0,
+      oversized: 0,
+      evictions: 0,
+      detaches: 1,
+      outstanding: 0,
+      peak_outstanding: 4,
+      pending_resets: 0
+    }, stats);
+  }
+
+  #[test]
+  fn test_stats_disabled() {
+    let str_pool : Pool<String> = pool()
+        .with(StartingSize(1))
+        .with(CollectStats(false))
+        .build();
+    {
+      let _a = str_pool.new();
+      let _b = str_pool.new();
+    }
+    assert_eq!(PoolStats::default(), str_pool.stats());
+  }
+
+  #[test]
+  fn test_observer() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    struct Recorder(Rc<RefCell<Vec<String>>>);
+    impl PoolObserver<String> for Recorder {
+      fn on_create(&mut self, _value: &String) {
+        self.0.borrow_mut().push("create".to_owned());
+      }
+      fn on_checkout(&mut self, _value: &mut String) {
+        self.0.borrow_mut().push("checkout".to_owned());
+      }
+      fn on_return(&mut self, value: &mut String) {
+        self.0.borrow_mut().push(format!("return {}", value));
+      }
+      fn on_discard(&mut self, value: &String) {
+        self.0.borrow_mut().push(format!("discard {}", value));
+      }
+    }
+
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let str_pool : Pool<String> = pool()
+        .with(StartingSize(1))
+        .with(MaxSize(1))
+        .with(Observer(Recorder(events.clone())))
+        .build();
+    {
+      let _cat = str_pool.new_from("cat");
+      let _dog = str_pool.new_from("dog");
+    }
+    assert_eq!(vec!["create", "checkout", "create", "checkout", "return dog", "discard cat"],
+               *events.borrow());
+  }
+
+  #[test]
+  fn test_observer_attach_and_detached() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    struct Counter(Rc<Cell<usize>>, Rc<Cell<usize>>);
+    impl PoolObserver<String> for Counter {
+      fn on_checkout(&mut self, _value: &mut String) {
+        self.0.set(self.0.get() + 1);
+      }
+      fn on_return(&mut self, _value: &mut String) {
+        self.1.set(self.1.get() + 1);
+      }
+    }
+
+    let checkouts = Rc::new(Cell::new(0));
+    let returns = Rc::new(Cell::new(0));
+    let str_pool : Pool<String> = pool()
+        .with(Observer(Counter(checkouts.clone(), returns.clone())))
+        .build();
+    let _detached = str_pool.detached();
+    assert_eq!(1, checkouts.get());
+    {
+      let _attached = str_pool.attach("cat".to_owned());
+      let _attached_rc = str_pool.attach_rc("dog".to_owned());
+      assert_eq!(3, checkouts.get());
+      assert_eq!(2, str_pool.stats().outstanding);
+    }
+    // Every checkout of a smartpointer is matched by a return.
+    assert_eq!(2, returns.get());
+    assert_eq!(0, str_pool.stats().outstanding);
+  }
+
+  #[test]
+  fn test_validate_on_return() {
+    let str_pool : Pool<String> = pool()
+        .with(StartingSize(0))
+        .with(Validate(|value: &String| !value.contains("poison")))
+        .build();
+    {
+      let _good = str_pool.new_from("cat");
+      let _bad = str_pool.new_from("poisoned cat");
+    }
+    assert_eq!(1, str_pool.size());
+    let stats = str_pool.stats();
+    assert_eq!(1, stats.rejected);
+    assert_eq!(1, stats.returns);
+    assert_eq!(0, stats.outstanding);
+  }
+
+  #[test]
+  fn test_validate_on_checkout() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    let healthy = Rc::new(Cell::new(true));
+    let validator_healthy = healthy.clone();
+    let str_pool : Pool<String> = pool()
+        .with(StartingSize(2))
+        .with(Supplier(|| String::with_capacity(16)))
+        .with(Validate(move |_: &String| validator_healthy.get()))
+        .with(ValidateOnCheckout(true))
+        .build();
+    healthy.set(false);
+    // Both idle values are rejected, so a replacement is requested from the supplier.
+    let rstring = str_pool.new();
+    assert_eq!(16, rstring.capacity());
+    assert_eq!(0, str_pool.size());
+    let stats = str_pool.stats();
+    assert_eq!(2, stats.rejected);
+    assert_eq!(1, stats.misses);
+    assert_eq!(0, stats.hits);
+  }
+
+  #[test]
+  fn test_max_retained_capacity_shrinks() {
+    let str_pool : Pool<String> = pool()
+        .with(StartingSize(0))
+        .with(MaxRetainedCapacity(16))
+        .build();
+    {
+      let mut rstring = str_pool.new();
+      rstring.push_str(&"x".repeat(1024));
+    }
+    assert_eq!(1, str_pool.size());
+    let rstring = str_pool.new();
+    assert!(rstring.capacity() <= 16);
+    assert_eq!(1, str_pool.stats().oversized);
+  }
+
+  #[test]
+  fn test_max_retained_capacity_discards() {
+    let vec_pool : Pool<Vec<u8>> = pool()
+        .with(StartingSize(0))
+        .with(MaxRetainedCapacity(16))
+        .with(OversizePolicy::Discard)
+        .build();
+    {
+      let _small = vec_pool.new_from(0..3u8);
+      let mut large = vec_pool.new();
+      large.resize(1024, 0);
+    }
+    assert_eq!(1, vec_pool.size());
+    let stats = vec_pool.stats();
+    assert_eq!(1, stats.oversized);
+    assert_eq!(1, stats.returns);
+    assert_eq!(0, stats.outstanding);
+  }
+
+  #[test]
+  fn test_max_retained_bytes() {
+    let str_pool : Pool<String> = pool()
+        .with(StartingSize(4))
+        .with(Supplier(|| String::with_capacity(32)))
+        .with(MaxRetainedBytes(64))
+        .build();
+    assert_eq!(2, str_pool.size());
+    assert_eq!(64, str_pool.retained_bytes());
+    assert_eq!(Some(64), str_pool.max_retained_bytes());
+    {
+      let _a = str_pool.new();
+      let _b = str_pool.new();
+      let _c = str_pool.new();
+      assert_eq!(0, str_pool.retained_bytes());
+    }
+    assert_eq!(2, str_pool.size());
+    assert_eq!(64, str_pool.retained_bytes());
+    assert_eq!(1, str_pool.stats().discards);
+  }
+
+  #[test]
+  fn test_heap_size_of_composite_types() {
+    use std::io::Cursor;
+    let pair = (String::with_capacity(8), Vec::<u32>::with_capacity(4));
+    assert_eq!(24, pair.heap_size());
+    assert_eq!(24, Some(pair).heap_size());
+    assert_eq!(0, None::<String>.heap_size());
+    let boxed = Box::new(String::with_capacity(8));
+    assert_eq!(std::mem::size_of::<String>() + 8, boxed.heap_size());
+    let strings = [String::with_capacity(4), String::with_capacity(12)];
+    assert_eq!(16, strings.heap_size());
+    assert_eq!(10, Cursor::new(Vec::<u8>::with_capacity(10)).heap_size());
+
+    let buffer_pool : Pool<Box<Vec<u8>>> = pool()
+        .with(StartingSize(0))
+        .with(MaxRetainedCapacity(16))
+        .with(MaxRetainedBytes(64))
+        .build();
+    buffer_pool.attach(Box::new(Vec::with_capacity(64)));
+    // The oversized buffer was shrunk before it was counted against the budget.
+    assert_eq!(1, buffer_pool.size());
+    assert!(buffer_pool.retained_bytes() <= std::mem::size_of::<Vec<u8>>() + 16);
+  }
+
+  #[test]
+  fn test_evict_idle() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use std::time::{Duration, Instant};
+    let now = Rc::new(Cell::new(Instant::now()));
+    let clock_now = now.clone();
+    let str_pool : Pool<String> = pool()
+        .with(StartingSize(2))
+        .with(MaxIdleTime(Duration::from_secs(60)))
+        .with(TimeSource(move || clock_now.get()))
+        .build();
+    now.set(now.get() + Duration::from_secs(30));
+    {
+      let _rstring = str_pool.new_from("cat");
+    }
+    assert_eq!(2, str_pool.size());
+    // The value created by the pool has now been idle for too long, the returned one has not.
+    now.set(now.get() + Duration::from_secs(45));
+    assert_eq!(1, str_pool.evict_idle());
+    assert_eq!(1, str_pool.size());
+    // Eviction also happens lazily on checkout.
+    now.set(now.get() + Duration::from_secs(60));
+    let _rstring = str_pool.new();
+    assert_eq!(0, str_pool.size());
+    let stats = str_pool.stats();
+    assert_eq!(2, stats.evictions);
+    assert_eq!(1, stats.misses);
+  }
+
+  #[test]
+  fn test_resize() {
+    let str_pool : Pool<String> = Pool::with_size_and_max(4, 8);
+    let outstanding = str_pool.new_rc_from("cat");
+    str_pool.reserve(10);
+    assert_eq!(8, str_pool.size());
+    str_pool.shrink_to(5);
+    assert_eq!(5, str_pool.size());
+    assert_eq!(8, str_pool.max_size());
+    str_pool.set_max_size(2);
+    assert_eq!(2, str_pool.size());
+    assert_eq!(2, str_pool.max_size());
+    str_pool.clear();
+    assert_eq!(0, str_pool.size());
+    drop(outstanding);
+    assert_eq!(1, str_pool.size());
+  }
+
+  #[test]
+  fn test_try_new() {
+    let str_pool : Pool<String> = Pool::with_size(1);
+    {
+      let rstring = str_pool.try_new_from("cat");
+      assert_eq!(Some("cat"), rstring.as_ref().map(|s| s.as_str()));
+      assert!(str_pool.try_new().is_none());
+    }
+    assert!(str_pool.try_new().is_some());
+    assert_eq!(0, str_pool.stats().misses);
+  }
+
+  #[test]
+  #[cfg(debug_assertions)]
+  #[should_panic(expected = "forbids creating new values")]
+  fn test_allocation_forbidden() {
+    let str_pool : Pool<String> = pool()
+        .with(StartingSize(1))
+        .with(AllocationPolicy::Forbid)
+        .build();
+    let _cat = str_pool.new_from("cat");
+    let _dog = str_pool.new_from("dog");
+  }
+
+  #[test]
+  fn test_allocation_forbidden_try_get() {
+    let str_pool : Pool<String> = pool()
+        .with(StartingSize(1))
+        .with(AllocationPolicy::Forbid)
+        .build();
+    let cat = str_pool.try_get_from("cat").unwrap();
+    assert_eq!(PoolError::Exhausted, str_pool.try_get().map(|value| value.detach()).unwrap_err());
+    drop(cat);
+    assert!(str_pool.try_get().is_ok());
+    assert_eq!(0, str_pool.stats().misses);
+  }
+
+  #[test]
+  fn test_try_supplier() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    let remaining = Rc::new(Cell::new(2));
+    let supplier_remaining = remaining.clone();
+    let str_pool : Pool<String, &'static str> = try_pool()
+        .with(StartingSize(1))
+        .with(TrySupplier(move || {
+          match supplier_remaining.get() {
+            0 => Err("daemon unavailable"),
+            n => {
+              supplier_remaining.set(n - 1);
+              Ok(String::with_capacity(16))
+            }
+          }
+        }))
+        .try_build()
+        .unwrap();
+    let first = str_pool.try_get_from("cat").unwrap();
+    let second = str_pool.try_get().unwrap();
+    assert_eq!(16, second.capacity());
+    assert_eq!(Err(PoolError::Supply("daemon unavailable")), str_pool.try_get().map(|_| ()));
+    drop(first);
+    assert_eq!("", *str_pool.try_get().unwrap());
+    drop(second);
+    assert_eq!(0, remaining.get());
+  }
+
+  #[test]
+  fn test_try_build() {
+    let result : Result<Pool<String, String>, _> = try_pool()
+        .with(StartingSize(4))
+        .with(TrySupplier(|| Err("no space left".to_owned())))
+        .try_build();
+    match result {
+      Err(error) => assert_eq!("failed to create a new value: no space left", error.to_string()),
+      Ok(_) => panic!("expected the prefill to fail")
+    }
+  }
+
+  #[test]
+  fn test_try_pool_builder_fields() {
+    let text = "cat dog".to_owned();
+    let mut builder = try_pool::<Vec<&str>, ()>()
+        .with(StartingSize(0))
+        .with(TrySupplier(|| Err(())));
+    builder.supplier = BoxedTrySupply::new(|| Ok(Vec::with_capacity(2)));
+    let word_pool = builder.try_build().unwrap();
+    let words = word_pool.try_get_from(text.split(' ')).unwrap();
+    assert_eq!(vec!["cat", "dog"], *words);
+  }
+
+  #[test]
+  fn test_try_reserve() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    let remaining = Rc::new(Cell::new(3));
+    let supplier_remaining = remaining.clone();
+    let str_pool : Pool<String, &'static str> = try_pool()
+        .with(StartingSize(0))
+        .with(TrySupplier(move || {
+          match supplier_remaining.get() {
+            0 => Err("daemon unavailable"),
+            n => {
+              supplier_remaining.set(n - 1);
+              Ok(String::new())
+            }
+          }
+        }))
+        .try_build()
+        .unwrap();
+    assert_eq!(Ok(()), str_pool.try_reserve(2));
+    assert_eq!(2, str_pool.size());
+    assert_eq!(Err(PoolError::Supply("daemon unavailable")), str_pool.try_reserve(2));
+    assert_eq!(3, str_pool.size());
+    assert_eq!(0, remaining.get());
+  }
+
+  #[test]
+  fn test_hash_map_with_custom_hasher() {
+    use std::collections::HashMap;
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::BuildHasherDefault;
+    type Map = HashMap<String, u32, BuildHasherDefault<DefaultHasher>>;
+    let buffer_pool : Pool<Map> = Pool::with_size(1);
+    let capacity = {
+      let map = buffer_pool.new_from((0..100).map(|n| (n.to_string(), n)));
+      assert_eq!(Some(&42), map.get("42"));
+      map.capacity()
+    };
+    let map = buffer_pool.new();
+    assert!(map.is_empty());
+    assert_eq!(capacity, map.capacity());
+  }
+
+  #[test]
+  fn test_std_types() {
+    use std::collections::{BTreeMap, BinaryHeap};
+    use std::io::{Cursor, Read};
+    use std::path::PathBuf;
+    let path_pool : Pool<PathBuf> = Pool::with_size(1);
+    {
+      let path = path_pool.new_from("/tmp/lifeguard");
+      assert_eq!("/tmp/lifeguard", path.to_str().unwrap());
+    }
+    assert!(path_pool.new().as_os_str().is_empty());
+
+    let cursor_pool : Pool<Cursor<Vec<u8>>> = Pool::with_size(1);
+    {
+      let mut cursor = cursor_pool.new_from(b"abc");
+      let mut buffer = String::new();
+      cursor.read_to_string(&mut buffer).unwrap();
+      assert_eq!("abc", buffer);
+    }
+    let cursor = cursor_pool.new();
+    assert_eq!(0, cursor.position());
+    assert!(cursor.get_ref().is_empty());
+
+    let heap_pool : Pool<BinaryHeap<u32>> = Pool::with_size(1);
+    assert_eq!(Some(&3), heap_pool.new_from(vec![1, 3, 2].into_iter()).peek());
+    let tree_pool : Pool<BTreeMap<u32, char>> = Pool::with_size(1);
+    assert_eq!(Some(&'b'), tree_pool.new_from(vec![(1, 'a'), (2, 'b')].into_iter()).get(&2));
+  }
+
+  #[test]
+  fn test_tuples_and_arrays() {
+    let tuple_pool : Pool<(Vec<u8>, String)> = Pool::with_size(1);
+    {
+      let scratch = tuple_pool.new_from((1..4u8, "cat"));
+      assert_eq!(vec![1, 2, 3], scratch.0);
+      assert_eq!("cat", scratch.1);
+    }
+    let scratch = tuple_pool.new();
+    assert!(scratch.0.is_empty() && scratch.0.capacity() >= 3);
+    assert!(scratch.1.is_empty());
+
+    let array_pool : Pool<[Vec<f32>; 4]> = Pool::with_size(1);
+    {
+      let mut buffers = array_pool.new();
+      buffers[3].push(1.0);
+    }
+    assert!(array_pool.new().iter().all(|buffer| buffer.is_empty()));
+    let array_pool : Pool<[String; 2]> = Pool::with_size(1);
+    assert_eq!(["cat", "dog"], *array_pool.new_from(["cat", "dog"]));
+  }
+
+  #[test]
+  fn test_box_and_option() {
+    let box_pool : Pool<Box<String>> = Pool::with_size(1);
+    {
+      let boxed = box_pool.new_from("cat");
+      assert_eq!("cat", **boxed);
+    }
+    assert!(box_pool.new().is_empty());
+
+    let option_pool : Pool<Option<String>> = Pool::with_size(1);
+    assert!(option_pool.new().is_none());
+    let capacity = {
+      let option = option_pool.new_from("cat");
+      assert_eq!(Some("cat"), option.as_deref());
+      (*option).as_ref().unwrap().capacity()
+    };
+    // The string inside is reset and kept, along with its buffer.
+    let option = option_pool.new();
+    assert_eq!(Some(""), option.as_deref());
+    assert_eq!(capacity, (*option).as_ref().unwrap().capacity());
+  }
+
+  // Stands in for a type from another crate that can't implement `Recycleable`.
+  struct Connection {
+    buffer: Vec<u8>,
+    uses: u32
+  }
+
+  #[test]
+  fn test_resetter() {
+    let connection_pool : Pool<Connection> = custom_pool(
+          || Connection { buffer: Vec::with_capacity(64), uses: 0 },
+          |connection: &mut Connection| {
+            connection.buffer.clear();
+            connection.uses += 1;
+          })
+        .with(StartingSize(1))
+        .build();
+    {
+      let mut connection = connection_pool.new();
+      connection.buffer.extend_from_slice(b"GET /");
+    }
+    let connection = connection_pool.new();
+    assert!(connection.buffer.is_empty());
+    assert_eq!(64, connection.buffer.capacity());
+    assert_eq!(1, connection.uses);
+  }
+
+  #[test]
+  fn test_supplier_and_resetter_settings() {
+    let connection_pool : Pool<Connection> = pool()
+        .with(StartingSize(1))
+        .with(Supplier(|| Connection { buffer: Vec::with_capacity(64), uses: 0 }))
+        .with(Resetter(|connection: &mut Connection| {
+          connection.buffer.clear();
+          connection.uses += 1;
+        }))
+        .with(MaxSize(4))
+        .build();
+    {
+      let mut connection = connection_pool.new();
+      connection.buffer.extend_from_slice(b"GET /");
+    }
+    let connection = connection_pool.new();
+    assert!(connection.buffer.is_empty());
+    assert_eq!(1, connection.uses);
+  }
+
+  #[test]
+  fn test_reset_on_checkout() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    let resets = Rc::new(Cell::new(0));
+    let counter = resets.clone();
+    let str_pool : Pool<String> = pool()
+        .with(StartingSize(0))
+        .with(Resetter(move |value: &mut String| {
+          counter.set(counter.get() + 1);
+          value.clear();
+        }))
+        .with(ResetTiming::OnCheckout)
+        .build();
+    str_pool.new_from("cat");
+    assert_eq!(0, resets.get());
+    assert_eq!(1, str_pool.size());
+    assert_eq!(1, str_pool.stats().pending_resets);
+    let value = str_pool.new();
+    assert_eq!("", *value);
+    assert_eq!(1, resets.get());
+    assert_eq!(0, str_pool.stats().pending_resets);
+  }
+
+  #[test]
+  fn test_reset_deferred() {
+    let str_pool : Pool<String> = pool()
+        .with(StartingSize(0))
+        .with(ResetTiming::Deferred)
+        .build();
+    {
+      let _a = str_pool.new_from("cat");
+      let _b = str_pool.new_from("dog");
+    }
+    assert_eq!(2, str_pool.stats().pending_resets);
+    // Values waiting on a reset can't be checked out.
+    assert!(str_pool.try_new().is_none());
+    assert_eq!(2, str_pool.reset_pending());
+    assert_eq!(0, str_pool.stats().pending_resets);
+    assert_eq!(2, str_pool.size());
+    assert_eq!("", *str_pool.try_new().unwrap());
+  }
+
+  #[test]
+  fn test_deferred_reset_budgets() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use std::time::{Duration, Instant};
+    let str_pool : Pool<String> = pool()
+        .with(StartingSize(0))
+        .with(ResetTiming::OnCheckout)
+        .with(MaxRetainedBytes(64))
+        .build();
+    for _ in 0..3 {
+      str_pool.attach(String::with_capacity(32));
+    }
+    // Values waiting to be reset count against the budget too.
+    assert_eq!(2, str_pool.size());
+    assert_eq!(64, str_pool.retained_bytes());
+    assert_eq!(1, str_pool.stats().discards);
+    let value = str_pool.new();
+    assert_eq!(32, str_pool.retained_bytes());
+    drop(value);
+    assert_eq!(64, str_pool.retained_bytes());
+
+    let now = Rc::new(Cell::new(Instant::now()));
+    let clock_now = now.clone();
+    let str_pool : Pool<String> = pool()
+        .with(StartingSize(0))
+        .with(ResetTiming::Deferred)
+        .with(MaxIdleTime(Duration::from_secs(60)))
+        .with(TimeSource(move || clock_now.get()))
+        .build();
+    str_pool.new_from("cat");
+    now.set(now.get() + Duration::from_secs(30));
+    str_pool.new_from("dog");
+    now.set(now.get() + Duration::from_secs(45));
+    // Only the value returned first has been idle for too long.
+    assert_eq!(1, str_pool.evict_idle());
+    assert_eq!(1, str_pool.stats().pending_resets);
+    assert_eq!(1, str_pool.reset_pending());
+    assert_eq!(1, str_pool.size());
+  }
+
+  #[test]
+  fn test_reset_panic() {
+    let str_pool : Pool<String> = pool()
+        .with(StartingSize(0))
+        .with(Resetter(|value: &mut String| {
+          if value.as_str() == "poison" {
+            panic!("cannot reset");
+          }
+          value.clear();
+        }))
+        .build();
+    str_pool.new_from("poison");
+    str_pool.new_from("cat");
+    let stats = str_pool.stats();
+    assert_eq!(1, stats.poisoned);
+    assert_eq!(1, stats.returns);
+    assert_eq!(0, stats.outstanding);
+    assert_eq!(1, str_pool.size());
+    assert_eq!("", *str_pool.new());
+  }
+
+  #[test]
+  fn test_reset_panic_while_unwinding() {
+    use std::panic;
+    let str_pool : Pool<String> = pool()
+        .with(StartingSize(1))
+        .with(Resetter(|_: &mut String| panic!("cannot reset")))
+        .build();
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+      let _value = str_pool.new();
+      panic!("user code failed");
+    }));
+    assert!(result.is_err());
+    assert_eq!(1, str_pool.stats().poisoned);
+    assert_eq!(0, str_pool.size());
+  }
+
+  #[test]
+  fn test_supplier_panic() {
+    use std::panic;
+    let mut created = 0;
+    let str_pool : Pool<String> = pool()
+        .with(StartingSize(0))
+        .with(Supplier(move || {
+          created += 1;
+          if created == 1 {
+            panic!("cannot create");
+          }
+          String::new()
+        }))
+        .build();
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(|| str_pool.new().detach()));
+    assert!(result.is_err());
+    assert_eq!(0, str_pool.stats().outstanding);
+    // The pool is still usable afterwards.
+    let value = str_pool.new();
+    assert_eq!("", *value);
+    assert_eq!(1, str_pool.stats().outstanding);
+  }
+
+  struct Node {
+    children: Vec<RcRecycled<Node>>
+  }
+
+  impl Recycleable for Node {
+    fn new() -> Node {
+      Node { children: Vec::new() }
+    }
+    fn reset(&mut self) {
+      // Returns the children to the pool that the node is being returned to.
+      self.children.clear();
+    }
+  }
+
+  #[test]
+  fn test_reset_returns_children() {
+    let node_pool : Pool<Node> = Pool::with_size(0);
+    {
+      let mut root = node_pool.new_rc();
+      for _ in 0..2 {
+        let mut child = node_pool.new_rc();
+        child.children.push(node_pool.new_rc());
+        root.children.push(child);
+      }
+      assert_eq!(5, node_pool.stats().outstanding);
+    }
+    assert_eq!(5, node_pool.size());
+    let stats = node_pool.stats();
+    assert_eq!(5, stats.returns);
+    assert_eq!(0, stats.outstanding);
+  }
+
+  #[test]
+  fn test_reset_deep_tree() {
+    let node_pool : Pool<Node> = Pool::with_size(0);
+    {
+      let mut root = node_pool.new_rc();
+      for _ in 0..100_000 {
+        let mut parent = node_pool.new_rc();
+        parent.children.push(root);
+        root = parent;
+      }
+    }
+    assert_eq!(100_001, node_pool.size());
+  }
+
+  #[test]
+  fn test_supplier_returns_values() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    let held : Rc<RefCell<Vec<RcRecycled<String>>>> = Rc::new(RefCell::new(Vec::new()));
+    let supplier_held = held.clone();
+    let str_pool : Pool<String> = pool()
+        .with(StartingSize(0))
+        .with(Supplier(move || {
+          // Dropping these hands them back to the pool while it's creating a value.
+          supplier_held.borrow_mut().clear();
+          String::new()
+        }))
+        .build();
+    let values = vec![str_pool.new_rc_from("cat"), str_pool.new_rc_from("dog")];
+    held.borrow_mut().extend(values);
+    assert_eq!(0, str_pool.size());
+    let _detached = str_pool.detached();
+    assert_eq!(2, str_pool.size());
+    let values = vec![str_pool.new_rc(), str_pool.new_rc()];
+    held.borrow_mut().extend(values);
+    let _value = str_pool.new();
+    assert_eq!(2, str_pool.size());
+    assert_eq!(1, str_pool.stats().outstanding);
+  }
+
+  #[test]
+  fn test_supplier_uses_its_own_pool() {
+    use std::cell::RefCell;
+    use std::rc::{Rc, Weak};
+    let shared : Rc<RefCell<Weak<Pool<String>>>> = Rc::new(RefCell::new(Weak::new()));
+    let supplier_shared = shared.clone();
+    let str_pool : Rc<Pool<String>> = Rc::new(pool()
+        .with(StartingSize(0))
+        .with(Supplier(move || {
+          // The nested request is served by `String::new` instead.
+          let str_pool = supplier_shared.borrow().upgrade().unwrap();
+          let mut value = str_pool.detached();
+          value.push_str("supplied");
+          value
+        }))
+        .build());
+    *shared.borrow_mut() = Rc::downgrade(&str_pool);
+    assert_eq!("supplied", str_pool.detached());
+    assert_eq!("supplied", *str_pool.new());
+  }
+
+  #[test]
+  fn test_custom_supplier_uses_its_own_pool() {
+    use std::cell::RefCell;
+    use std::rc::{Rc, Weak};
+    let shared : Rc<RefCell<Weak<Pool<Connection>>>> = Rc::new(RefCell::new(Weak::new()));
+    let nested = Rc::new(RefCell::new(Vec::new()));
+    let (supplier_shared, supplier_nested) = (shared.clone(), nested.clone());
+    let connection_pool : Rc<Pool<Connection>> = Rc::new(custom_pool(
+          move || {
+            let connection_pool = supplier_shared.borrow().upgrade().unwrap();
+            let error = connection_pool.try_get().map(|connection| connection.uses).unwrap_err();
+            supplier_nested.borrow_mut().push(error);
+            Connection { buffer: Vec::new(), uses: 0 }
+          },
+          |connection: &mut Connection| connection.buffer.clear())
+        .with(StartingSize(0))
+        .build());
+    *shared.borrow_mut() = Rc::downgrade(&connection_pool);
+    assert_eq!(0, connection_pool.new().uses);
+    assert_eq!(vec![PoolError::Reentered], *nested.borrow());
+  }
+
+  #[test]
+  fn test_validator_returns_values() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    let held : Rc<RefCell<Vec<RcRecycled<String>>>> = Rc::new(RefCell::new(Vec::new()));
+    let validator_held = held.clone();
+    let str_pool : Pool<String> = pool()
+        .with(StartingSize(2))
+        .with(Validate(move |_: &String| {
+          if let Ok(mut held) = validator_held.try_borrow_mut() {
+            held.clear();
+          }
+          true
+        }))
+        .with(ValidateOnCheckout(true))
+        .build();
+    let value = str_pool.new_rc_from("cat");
+    held.borrow_mut().push(value);
+    // The value returned while the validator runs can't be validated itself.
+    let _value = str_pool.new();
+    assert_eq!(0, str_pool.size());
+    assert_eq!(1, str_pool.stats().rejected);
+  }
+
+  #[test]
+  fn test_clock_returns_values() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::time::{Duration, Instant};
+    let held : Rc<RefCell<Vec<RcRecycled<String>>>> = Rc::new(RefCell::new(Vec::new()));
+    let clock_held = held.clone();
+    let str_pool : Pool<String> = pool()
+        .with(StartingSize(0))
+        .with(MaxIdleTime(Duration::from_secs(60)))
+        .with(TimeSource(move || {
+          if let Ok(mut held) = clock_held.try_borrow_mut() {
+            held.clear();
+          }
+          Instant::now()
+        }))
+        .build();
+    let values = vec![str_pool.new_rc_from("cat"), str_pool.new_rc_from("dog")];
+    held.borrow_mut().extend(values);
+    // Both values are returned before the pool looks for one to check out.
+    assert_eq!("", *str_pool.new());
+    assert_eq!(2, str_pool.size());
+    assert_eq!(0, str_pool.stats().outstanding);
+  }
+
+  #[test]
+  fn test_try_custom_pool() {
+    let connection_pool : Pool<Connection, &'static str> = try_custom_pool(
+          || Err("connection refused"),
+          |connection: &mut Connection| connection.buffer.clear())
+        .with(StartingSize(0))
+        .try_build()
+        .unwrap();
+    assert_eq!(PoolError::Supply("connection refused"),
+               connection_pool.try_get().map(|connection| connection.uses).unwrap_err());
+  }
+
+  #[test]
+  fn test_sync_recycle_across_threads() {
+    use std::thread;
+    let str_pool : SyncPool<String> = SyncPool::with_size(2);
+    thread::scope(|scope| {
+      let first = str_pool.new_from("cat");
+      let second = str_pool.new_arc_from("dog");
+      assert_eq!(0, str_pool.size());
+      scope.spawn(move || {
+        assert_eq!("cat", *first);
+        assert_eq!("dog", *second);
+      });
+    });
+    assert_eq!(2, str_pool.size());
+    assert_eq!("", *str_pool.new());
+  }
+
+  #[test]
+  fn test_sync_shared_between_threads() {
+    use std::sync::Arc;
+    use std::thread;
+    let str_pool : Arc<SyncPool<String>> = Arc::new(SyncPool::with_size_and_max(0, 4));
+    let handles: Vec<_> = (0..4).map(|_| {
+      let str_pool = str_pool.clone();
+      thread::spawn(move || {
+        for _ in 0..100 {
+          let mut rstring = str_pool.new_arc();
+          rstring.push_str("cat");
+          assert_eq!("cat", *rstring);
+        }
+      })
+    }).collect();
+    for handle in handles {
+      handle.join().unwrap();
+    }
+    assert!(str_pool.size() <= 4);
+  }
+
+  #[test]
+  fn test_sync_detach_and_attach() {
+    let str_pool : SyncPool<String> = SyncPool::with_size(1);
+    {
+      let string: String = str_pool.detached();
+      assert_eq!(0, str_pool.size());
+      let _rstring: ArcRecycled<String> = str_pool.attach_arc(string);
+    }
+    assert_eq!(1, str_pool.size());
+  }
+
+  #[test]
+  fn test_sync_builder() {
+    let pool = sync_pool()
+        .with(StartingSize(128))
+        .with(MaxSize(1_024))
+        .with(Supplier(|| String::with_capacity(16_000)))
+        .build();
+    assert_eq!(pool.size(), 128);
+    assert_eq!(pool.max_size(), 1_024);
+    assert_eq!(pool.new().capacity(), 16_000);
+  }
+
+  #[test]
+  fn test_sync_try_get() {
+    let str_pool : SyncPool<String> = sync_pool()
+        .with(MaxOutstanding(1))
+        .build();
+    {
+      let _rstring = str_pool.try_get().unwrap();
+      assert_eq!(1, str_pool.outstanding());
+      assert_eq!(PoolError::Exhausted, str_pool.try_get().unwrap_err());
+    }
+    assert_eq!(0, str_pool.outstanding());
+    assert!(str_pool.try_get().is_ok());
+  }
+
+  #[test]
+  fn test_sync_get_timeout() {
+    use std::thread;
+    use std::time::Duration;
+    let str_pool : SyncPool<String> = sync_pool()
+        .with(MaxOutstanding(1))
+        .build();
+    let held = str_pool.new_arc();
+    let result = str_pool.get_timeout(Duration::from_millis(20));
+    assert_eq!(PoolError::TimedOut, result.unwrap_err());
+    thread::scope(|scope| {
+      scope.spawn(move || {
+        thread::sleep(Duration::from_millis(20));
+        drop(held);
+      });
+      assert!(str_pool.get_timeout(Duration::from_secs(60)).is_ok());
+    });
+    assert_eq!(0, str_pool.outstanding());
+  }
+
+  #[test]
+  fn test_sync_waiters_are_served_in_order() {
+    use std::sync::Mutex;
+    use std::thread;
+    use std::time::Duration;
+    let str_pool : SyncPool<String> = sync_pool()
+        .with(MaxOutstanding(1))
+        .build();
+    let order = Mutex::new(Vec::new());
+    let held = str_pool.try_get().unwrap();
+    thread::scope(|scope| {
+      for (index, name) in ["first", "second", "third"].iter().enumerate() {
+        let (str_pool, order) = (&str_pool, &order);
+        scope.spawn(move || {
+          let _rstring = str_pool.get_timeout(Duration::from_secs(60)).unwrap();
+          order.lock().unwrap().push(*name);
+        });
+        // Wait for each thread to join the line before starting the next one.
+        while str_pool.waiting() <= index {
+          thread::yield_now();
+        }
+      }
+      drop(held);
+    });
+    assert_eq!(vec!["first", "second", "third"], *order.lock().unwrap());
+  }
+
+  #[test]
+  fn test_sync_detach_frees_a_slot() {
+    let str_pool : SyncPool<String> = sync_pool()
+        .with(MaxOutstanding(1))
+        .build();
+    let _string : String = str_pool.new().detach();
+    assert_eq!(0, str_pool.outstanding());
+    assert!(str_pool.try_get().is_ok());
+  }
+
+  #[test]
+  fn test_sync_get_timeout_max() {
+    use std::thread;
+    use std::time::Duration;
+    let str_pool : SyncPool<String> = sync_pool()
+        .with(MaxOutstanding(1))
+        .build();
+    let held = str_pool.new_arc();
+    thread::scope(|scope| {
+      scope.spawn(|| {
+        while str_pool.waiting() == 0 {
+          thread::yield_now();
+        }
+        drop(held);
+      });
+      // A timeout too long to be represented waits forever.
+      assert!(str_pool.get_timeout(Duration::MAX).is_ok());
+    });
+  }
+
+  #[test]
+  fn test_sync_supplier_uses_its_own_pool() {
+    use std::sync::{Arc, OnceLock, Weak};
+    let shared : Arc<OnceLock<Weak<SyncPool<String>>>> = Arc::new(OnceLock::new());
+    let supplier_shared = shared.clone();
+    let str_pool : Arc<SyncPool<String>> = Arc::new(sync_pool()
+        .with(StartingSize(0))
+        .with(MaxOutstanding(2))
+        .with(Supplier(move || {
+          // The nested request is served by `String::new` instead of deadlocking.
+          let str_pool = supplier_shared.get().unwrap().upgrade().unwrap();
+          let mut value = str_pool.detached();
+          value.push_str("supplied");
+          value
+        }))
+        .build());
+    shared.set(Arc::downgrade(&str_pool)).unwrap();
+    assert_eq!("supplied", str_pool.detached());
+    assert_eq!("supplied", *str_pool.try_get().unwrap());
+    assert_eq!(0, str_pool.outstanding());
+  }
+
+  #[test]
+  fn test_sync_supplier_runs_unlocked() {
+    use std::sync::mpsc;
+    use std::sync::Mutex;
+    use std::thread;
+    let (started_tx, started_rx) = mpsc::channel();
+    let (finish_tx, finish_rx) = mpsc::channel::<()>();
+    let (started_tx, finish_rx) = (Mutex::new(started_tx), Mutex::new(finish_rx));
+    let str_pool : SyncPool<String> = sync_pool()
+        .with(StartingSize(0))
+        .with(Supplier(move || {
+          started_tx.lock().unwrap().send(()).unwrap();
+          finish_rx.lock().unwrap().recv().unwrap();
+          String::new()
+        }))
+        .build();
+    thread::scope(|scope| {
+      scope.spawn(|| str_pool.new().len());
+      started_rx.recv().unwrap();
+      // The pool can still be used while the supplier is busy.
+      drop(str_pool.attach("cat".to_owned()));
+      assert_eq!(1, str_pool.size());
+      finish_tx.send(()).unwrap();
+    });
+    assert_eq!(2, str_pool.size());
+  }
+
+  // A value whose reset panics when it holds "poison".
+  struct Brittle(String);
+
+  impl Recycleable for Brittle {
+    fn new() -> Brittle {
+      Brittle(String::new())
+    }
+    fn reset(&mut self) {
+      if self.0 == "poison" {
+        panic!("cannot reset");
+      }
+      self.0.clear();
+    }
+  }
+
+  #[test]
+  fn test_sync_supplier_panic_frees_a_slot() {
+    use std::panic;
+    let mut created = 0;
+    let str_pool : SyncPool<String> = sync_pool()
+        .with(StartingSize(0))
+        .with(MaxOutstanding(1))
+        .with(Supplier(move || {
+          created += 1;
+          if created == 1 {
+            panic!("cannot create");
+          }
+          String::new()
+        }))
+        .build();
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(|| str_pool.try_get().is_ok()));
+    assert!(result.is_err());
+    assert_eq!(0, str_pool.outstanding());
+    assert!(str_pool.try_get().is_ok());
+  }
+
+  #[test]
+  fn test_sync_reset_panic_frees_a_slot() {
+    let pool : SyncPool<Brittle> = sync_pool()
+        .with(StartingSize(0))
+        .with(MaxOutstanding(1))
+        .build();
+    // The panic is contained when the value is returned.
+    pool.try_get().unwrap().0.push_str("poison");
+    assert_eq!(0, pool.outstanding());
+    // The half reset value was dropped rather than kept.
+    assert_eq!(0, pool.size());
+    assert!(pool.try_get().is_ok());
+  }
+
+  #[test]
+  fn test_tiered_local_cache() {
+    let str_pool : TieredPool<String> = tiered_pool()
+        .with(StartingSize(0))
+        .with(LocalBatchSize(2))
+        .build();
+    {
+      let _a = str_pool.new_from("cat");
+      let _b = str_pool.new_from("dog");
+      let _c = str_pool.new_from("mouse");
+      let _d = str_pool.new_from("cheese");
+    }
+    // Returning the fourth value overflowed the local cache, moving one batch to the depot.
+    assert_eq!(2, str_pool.local_size());
+    assert_eq!(2, str_pool.size());
+    assert_eq!("", *str_pool.new());
+    assert_eq!(2, str_pool.local_size());
+  }
+
+  #[test]
+  fn test_tiered_batches_move_between_threads() {
+    use std::sync::Arc;
+    use std::thread;
+    let str_pool : Arc<TieredPool<String>> = Arc::new(tiered_pool()
+        .with(StartingSize(4))
+        .with(LocalBatchSize(2))
+        .build());
+    assert_eq!(4, str_pool.size());
+    let thread_pool = str_pool.clone();
+    thread::spawn(move || {
+      let rstring = thread_pool.new_from("cat");
+      // One batch was moved from the depot into this thread's cache.
+      assert_eq!(1, thread_pool.local_size());
+      assert_eq!(2, thread_pool.size());
+      drop(rstring);
+      assert_eq!(2, thread_pool.local_size());
+    }).join().unwrap();
+    // The exiting thread handed its cached values back to the depot.
+    assert_eq!(4, str_pool.size());
+    assert_eq!(0, str_pool.local_size());
+  }
+
+  #[test]
+  fn test_tiered_depot_capacity() {
+    let str_pool : TieredPool<String> = tiered_pool()
+        .with(StartingSize(10))
+        .with(LocalBatchSize(1))
+        .with(DepotCapacity(3))
+        .build();
+    assert_eq!(3, str_pool.size());
+    assert_eq!(3, str_pool.depot_capacity());
+    {
+      let values: Vec<_> = (0..6).map(|_| str_pool.new()).collect();
+      assert_eq!(0, str_pool.size());
+      drop(values);
+    }
+    assert_eq!(1, str_pool.local_size());
+    assert_eq!(3, str_pool.size());
+  }
+
+  #[test]
+  fn test_tiered_drop_frees_other_threads_caches() {
+    use std::sync::{mpsc, Arc};
+    use std::thread;
+
+    struct Tracked(#[allow(dead_code)] Arc<()>);
+    impl Recycleable for Tracked {
+      fn new() -> Tracked { Tracked(Arc::new(())) }
+      fn reset(&mut self) {}
+    }
+
+    let marker = Arc::new(());
+    let supplied = marker.clone();
+    let pool : Arc<TieredPool<Tracked>> = Arc::new(tiered_pool()
+        .with(StartingSize(0))
+        .with(Supplier(move || Tracked(supplied.clone())))
+        .build());
+    let thread_pool = pool.clone();
+    let (cached_tx, cached_rx) = mpsc::channel();
+    let (dropped_tx, dropped_rx) = mpsc::channel();
+    let (swept_tx, swept_rx) = mpsc::channel();
+    let (exit_tx, exit_rx) = mpsc::channel::<()>();
+    let handle = thread::spawn(move || {
+      drop(thread_pool.new());
+      assert_eq!(1, thread_pool.local_size());
+      drop(thread_pool);
+      cached_tx.send(()).unwrap();
+      dropped_rx.recv().unwrap();
+      // Touching any other tiered pool frees the values cached for the dropped one.
+      let str_pool : TieredPool<String> = tiered_pool().build();
+      drop(str_pool.new());
+      swept_tx.send(()).unwrap();
+      let _ = exit_rx.recv();
+    });
+    cached_rx.recv().unwrap();
+    // The supplier and the value cached by the other thread.
+    assert_eq!(3, Arc::strong_count(&marker));
+    drop(pool);
+    assert_eq!(2, Arc::strong_count(&marker));
+    dropped_tx.send(()).unwrap();
+    swept_rx.recv().unwrap();
+    assert_eq!(1, Arc::strong_count(&marker));
+    drop(exit_tx);
+    handle.join().unwrap();
+  }
+
+  // A minimal executor for driving `AsyncPool` futures to completion.
+  fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake};
+    use std::thread::{self, Thread};
+
+    struct ThreadWaker(Thread);
+    impl Wake for ThreadWaker {
+      fn wake(self: Arc<Self>) {
+        self.0.unpark();
+      }
+    }
+
+    let mut future = Box::pin(future);
+    let waker = Arc::new(ThreadWaker(thread::current())).into();
+    let mut cx = Context::from_waker(&waker);
+    loop {
+      match future.as_mut().poll(&mut cx) {
+        Poll::Ready(output) => return output,
+        Poll::Pending => thread::park()
+      }
+    }
+  }
+
+  fn poll_once<F: std::future::Future + Unpin>(future: &mut F) -> std::task::Poll<F::Output> {
+    use std::pin::Pin;
+    use std::task::{Context, Waker};
+    Pin::new(future).poll(&mut Context::from_waker(Waker::noop()))
+  }
+
+  #[test]
+  fn test_async_acquire() {
+    let str_pool : AsyncPool<String> = async_pool()
+        .with(StartingSize(1))
+        .with(MaxOutstanding(2))
+        .build();
+    {
+      let first = block_on(str_pool.acquire());
+      let _second = block_on(str_pool.acquire());
+      assert_eq!("", *first);
+      assert_eq!(2, str_pool.outstanding());
+      assert_eq!(0, str_pool.size());
+      assert_eq!(PoolError::Exhausted, str_pool.try_acquire().unwrap_err());
+    }
+    assert_eq!(0, str_pool.outstanding());
+    assert_eq!(2, str_pool.size());
+    assert_eq!("cat", *str_pool.try_acquire_from("cat").unwrap());
+  }
+
+  #[test]
+  fn test_async_waiters_are_served_in_order() {
+    let str_pool : AsyncPool<String> = async_pool()
+        .with(MaxOutstanding(1))
+        .build();
+    let held = str_pool.try_acquire().unwrap();
+    let mut first = str_pool.acquire();
+    let mut second = str_pool.acquire();
+    assert!(poll_once(&mut first).is_pending());
+    assert!(poll_once(&mut second).is_pending());
+    // Newcomers may not jump the queue.
+    assert_eq!(PoolError::Exhausted, str_pool.try_acquire().unwrap_err());
+    drop(held);
+    assert!(poll_once(&mut second).is_pending());
+    let value = match poll_once(&mut first) {
+      std::task::Poll::Ready(value) => value,
+      std::task::Poll::Pending => panic!("The first waiter should have been served.")
+    };
+    drop(value);
+    assert!(poll_once(&mut second).is_ready());
+  }
+
+  #[test]
+  fn test_async_cancelled_waiter_passes_its_turn_on() {
+    let str_pool : AsyncPool<String> = async_pool()
+        .with(StartingSize(0))
+        .with(MaxOutstanding(1))
+        .build();
+    let held = str_pool.try_acquire().unwrap();
+    let mut first = str_pool.acquire();
+    let mut second = str_pool.acquire();
+    assert!(poll_once(&mut first).is_pending());
+    assert!(poll_once(&mut second).is_pending());
+    drop(held);
+    // The first waiter was handed the slot but gave up before claiming it.
+    drop(first);
+    assert!(poll_once(&mut second).is_ready());
+    assert_eq!(0, str_pool.outstanding());
+    assert_eq!(1, str_pool.size());
+  }
+
+  #[test]
+  fn test_async_acquire_timeout() {
+    use std::thread;
+    use std::time::Duration;
+    let str_pool : AsyncPool<String> = async_pool()
+        .with(MaxOutstanding(1))
+        .build();
+    let held = str_pool.try_acquire().unwrap();
+    let result = block_on(str_pool.acquire_timeout(Duration::from_millis(20)));
+    assert_eq!(PoolError::TimedOut, result.unwrap_err());
+    let wait = str_pool.acquire_timeout(Duration::from_secs(60));
+    thread::scope(|scope| {
+      scope.spawn(move || {
+        thread::sleep(Duration::from_millis(20));
+        drop(held);
+      });
+      assert!(block_on(wait).is_ok());
+    });
+  }
+
+  #[test]
+  fn test_async_acquire_timeout_max() {
+    use std::time::Duration;
+    let str_pool : AsyncPool<String> = async_pool()
+        .with(MaxOutstanding(1))
+        .build();
+    let held = str_pool.try_acquire().unwrap();
+    // A timeout too long to be represented waits forever.
+    let mut wait = str_pool.acquire_timeout(Duration::MAX);
+    assert!(poll_once(&mut wait).is_pending());
+    drop(held);
+    match poll_once(&mut wait) {
+      std::task::Poll::Ready(result) => assert!(result.is_ok()),
+      std::task::Poll::Pending => panic!("The waiter should have been served.")
+    }
+  }
+
+  #[test]
+  fn test_async_acquire_timeout_releases_its_timer() {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::task::{Context, Wake, Waker};
+    use std::time::Duration;
+
+    // A waker whose copies can be counted, unlike `Waker::noop()`.
+    struct CountingWaker(AtomicUsize);
+    impl Wake for CountingWaker {
+      fn wake(self: Arc<Self>) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+      }
+    }
+
+    let str_pool : AsyncPool<String> = async_pool()
+        .with(MaxOutstanding(1))
+        .build();
+    let _held = str_pool.try_acquire().unwrap();
+    let inner = Arc::new(CountingWaker(AtomicUsize::new(0)));
+    let waker : Waker = inner.clone().into();
+    let mut wait = str_pool.acquire_timeout(Duration::from_secs(60));
+    for _ in 0..10 {
+      assert!(Pin::new(&mut wait).poll(&mut Context::from_waker(&waker)).is_pending());
+    }
+    // One copy is kept in the queue of waiting tasks and one by the timer.
+    assert_eq!(4, Arc::strong_count(&inner));
+    drop(wait);
+    assert_eq!(2, Arc::strong_count(&inner));
+  }
+
+  #[test]
+  fn test_async_detach_frees_a_slot() {
+    let str_pool : AsyncPool<String> = async_pool()
+        .with(MaxOutstanding(1))
+        .build();
+    let _string : String = str_pool.try_acquire().unwrap().detach();
+    assert_eq!(0, str_pool.outstanding());
+    assert!(str_pool.try_acquire().is_ok());
+  }
+
+  #[test]
+  fn test_async_supplier_panic_frees_a_slot() {
+    use std::panic;
+    let mut created = 0;
+    let str_pool : AsyncPool<String> = async_pool()
+        .with(StartingSize(0))
+        .with(MaxOutstanding(1))
+        .with(Supplier(move || {
+          created += 1;
+          if created == 1 {
+            panic!("cannot create");
+          }
+          String::new()
+        }))
+        .build();
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(|| str_pool.try_acquire().is_ok()));
+    assert!(result.is_err());
+    assert_eq!(0, str_pool.outstanding());
+    assert!(block_on(str_pool.acquire()).is_empty());
+  }
+
+  #[test]
+  fn test_async_reset_panic_frees_a_slot() {
+    let pool : AsyncPool<Brittle> = async_pool()
+        .with(StartingSize(0))
+        .with(MaxOutstanding(1))
+        .build();
+    // The panic is contained when the value is returned.
+    pool.try_acquire().unwrap().0.push_str("poison");
+    assert_eq!(0, pool.outstanding());
+    assert_eq!(0, pool.size());
+    assert!(pool.try_acquire().is_ok());
+  }
+
+  #[test]
+  fn test_async_supplier_runs_unlocked() {
+    use std::sync::mpsc;
+    use std::sync::Mutex;
+    use std::thread;
+    let (started_tx, started_rx) = mpsc::channel();
+    let (finish_tx, finish_rx) = mpsc::channel::<()>();
+    let (started_tx, finish_rx) = (Mutex::new(started_tx), Mutex::new(finish_rx));
+    let str_pool : AsyncPool<String> = async_pool()
+        .with(StartingSize(0))
+        .with(Supplier(move || {
+          started_tx.lock().unwrap().send(()).unwrap();
+          finish_rx.lock().unwrap().recv().unwrap();
+          String::new()
+        }))
+        .build();
+    thread::scope(|scope| {
+      scope.spawn(|| str_pool.try_acquire().unwrap().len());
+      started_rx.recv().unwrap();
+      // The pool can still be used while the supplier is busy.
+      drop(str_pool.attach("cat".to_owned()));
+      assert_eq!(1, str_pool.size());
+      finish_tx.send(()).unwrap();
+    });
+    assert_eq!(2, str_pool.size());
+  }
+
+  #[test]
+  fn test_outstanding_report() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use std::time::{Duration, Instant};
+    let now = Rc::new(Cell::new(Instant::now()));
+    let clock_now = now.clone();
+    let str_pool : Pool<String> = pool()
+        .with(TrackCheckouts(true))
+        .with(TimeSource(move || clock_now.get()))
+        .build();
+    let held : Vec<RcRecycled<String>> = (0..2).map(|_| str_pool.new_rc()).collect();
+    let line = line!() - 1;
+    now.set(now.get() + Duration::from_secs(30));
+    let recent = str_pool.new();
+    let detached = str_pool.new_rc().detach();
+
+    let report = str_pool.outstanding_report(Duration::from_secs(10));
+    assert_eq!(1, report.len());
+    assert_eq!(2, report[0].count);
+    assert_eq!(Duration::from_secs(30), report[0].oldest);
+    assert_eq!(line, report[0].location.line());
+    assert!(report[0].location.file().ends_with("lib.rs"));
+    assert_eq!(2, str_pool.outstanding_report(Duration::from_secs(0)).len());
+
+    drop(held);
+    drop(recent);
+    drop(detached);
+    assert!(str_pool.outstanding_report(Duration::from_secs(0)).is_empty());
+  }
+
+  #[test]
+  fn test_checkout_tracking_is_opt_in() {
+    use std::time::Duration;
+    let str_pool : Pool<String> = Pool::with_size(1);
+    let _value = str_pool.new();
+    assert!(str_pool.outstanding_report(Duration::from_secs(0)).is_empty());
+  }
+
+  #[test]
+  fn test_observer_on_leak() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    struct Leaks(Rc<RefCell<Vec<(usize, usize)>>>);
+    impl PoolObserver<String> for Leaks {
+      fn on_leak(&mut self, outstanding: usize, sites: &[OutstandingCheckouts]) {
+        let sites = sites.iter().map(|site| site.count).sum();
+        self.0.borrow_mut().push((outstanding, sites));
+      }
+    }
+
+    let leaks = Rc::new(RefCell::new(Vec::new()));
+    let str_pool : Pool<String> = pool()
+        .with(TrackCheckouts(true))
+        .with(Observer(Leaks(leaks.clone())))
+        .build();
+    let held = vec![str_pool.new_rc(), str_pool.new_rc()];
+    drop(str_pool);
+    assert_eq!(vec![(2, 2)], *leaks.borrow());
+    drop(held);
+
+    let str_pool : Pool<String> = pool()
+        .with(Observer(Leaks(leaks.clone())))
+        .build();
+    drop(str_pool);
+    assert_eq!(1, leaks.borrow().len());
+  }
+
+  #[test]
+  fn test_return_to_dropped_pool() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    struct Discards(Rc<Cell<usize>>);
+    impl PoolObserver<String> for Discards {
+      fn on_discard(&mut self, _value: &String) {
+        self.0.set(self.0.get() + 1);
+      }
+    }
+
+    let discards = Rc::new(Cell::new(0));
+    let str_pool : Pool<String> = pool()
+        .with(StartingSize(3))
+        .with(Observer(Discards(discards.clone())))
+        .with(ReturnToDroppedPool::Discard)
+        .build();
+    let value = str_pool.new_rc_from("cat");
+    drop(str_pool);
+    // The idle values are dropped with the pool...
+    assert_eq!(2, discards.get());
+    // ...and the outstanding one when it's returned.
+    drop(value);
+    assert_eq!(3, discards.get());
+  }
+
+  #[test]
+  fn test_clone_after_pool_dropped() {
+    let str_pool : Pool<String> = pool()
+        .with(StartingSize(1))
+        .with(ReturnToDroppedPool::Discard)
+        .build();
+    let value = str_pool.new_rc_from("cat");
+    drop(str_pool);
+    let cloned = value.clone();
+    assert_eq!("cat", *cloned);
+    drop(value);
+    assert_eq!("cat", *cloned);
+  }
+
+  #[test]
+  fn test_close_and_drain() {
+    let str_pool : Pool<String> = Pool::with_size(2);
+    let value = str_pool.new_from("cat");
+    str_pool.close();
+    assert!(str_pool.is_closed());
+    assert_eq!(Err(PoolError::Closed), str_pool.try_get().map(|value| value.detach()));
+    assert!(str_pool.try_new().is_none());
+    drop(value);
+    // The returned value was dropped rather than stored.
+    assert_eq!(1, str_pool.size());
+    assert_eq!(vec![String::new()], str_pool.drain().collect::<Vec<_>>());
+    assert_eq!(0, str_pool.size());
+  }
+
+  #[test]
+  #[should_panic(expected = "the pool has been closed")]
+  fn test_closed_new_panics() {
+    let str_pool : Pool<String> = Pool::with_size(1);
+    str_pool.close();
+    let _value = str_pool.new();
+  }
+
+  #[test]
+  fn test_clone_after_close() {
+    let str_pool : Pool<String> = Pool::with_size(1);
+    let value = str_pool.new_from("cat");
+    let rc_value = str_pool.new_rc_from("dog");
+    str_pool.close();
+    assert_eq!("cat", *value.clone());
+    assert_eq!("dog", *rc_value.clone());
+    drop(value);
+    drop(rc_value);
+    assert_eq!(0, str_pool.size());
+    assert_eq!(0, str_pool.stats().outstanding);
+  }
+
+  #[test]
+  fn test_reserve_after_close() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    let created = Rc::new(Cell::new(0));
+    let counter = created.clone();
+    let str_pool : Pool<String> = pool()
+        .with(StartingSize(1))
+        .with(Supplier(move || {
+          counter.set(counter.get() + 1);
+          String::new()
+        }))
+        .build();
+    str_pool.close();
+    str_pool.reserve(4);
+    assert!(str_pool.try_reserve(4).is_ok());
+    assert_eq!(1, created.get());
+    assert_eq!(1, str_pool.size());
+  }
+
+  #[test]
+  fn test_into_values() {
+    let str_pool : Pool<String> = pool()
+        .with(StartingSize(0))
+        .with(ResetTiming::Deferred)
+        .build();
+    str_pool.new_from("cat");
+    str_pool.attach("dog".to_owned());
+    let values = str_pool.into_values();
+    assert_eq!(vec![String::new(), String::new()], values);
+  }
+
+  #[test]
+  fn test_wait_until_all_returned() {
+    let str_pool : Pool<String> = Pool::with_size(1);
+    assert!(poll_once(&mut str_pool.wait_until_all_returned()).is_ready());
+    let first = str_pool.new_rc();
+    let second = str_pool.new();
+    let mut all_returned = str_pool.wait_until_all_returned();
+    assert!(poll_once(&mut all_returned).is_pending());
+    drop(first);
+    assert!(poll_once(&mut all_returned).is_pending());
+    let _detached = second.detach();
+    assert!(poll_once(&mut all_returned).is_ready());
+  }
 }